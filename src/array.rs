@@ -0,0 +1,89 @@
+use crate::{BinaryOp,BitVec,MachineWord,Term,TermKind};
+
+/// A symbolic array whose indices and elements are terms of the same
+/// width, following the theory of arrays.  An array is represented
+/// as a chain of stores over an array whose elements are all zero.
+/// Selecting an element is eliminated using _read-over-write_, such
+/// that the result is an ordinary term (i.e. a conditional over the
+/// stores which may alias the index).  Stores and selects whose
+/// indices are known to alias (or not) are resolved immediately and,
+/// hence, an array whose indices are all known is concrete.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Array {
+    /// Stores applied to this array, from oldest to most recent.
+    stores: Vec<(Term,Term)>
+}
+
+impl Array {
+    pub fn new() -> Self {
+        Self{stores: Vec::new()}
+    }
+
+    /// Get the stores applied to this array, from oldest to most
+    /// recent.  Stores which have been overwritten are omitted.
+    pub fn stores(&self) -> &[(Term,Term)] {
+        &self.stores
+    }
+
+    /// Store a given value at a given index.
+    pub fn store(&mut self, index: Term, value: Term) {
+        // Any store definitely overwritten is redundant.
+        self.stores.retain(|(i,_)| alias(i,&index) != Some(true));
+        self.stores.push((index,value));
+    }
+
+    /// Select the element at a given index.
+    pub fn select(&self, index: &Term) -> Term {
+        let mut value = index.constant_like(0);
+        let mut pending = Vec::new();
+        for (i,v) in self.stores.iter().rev() {
+            match alias(i,index) {
+                Some(true) => { value = v.clone(); break; }
+                Some(false) => {}
+                None => pending.push((i,v))
+            }
+        }
+        for (i,v) in pending.into_iter().rev() {
+            let c = i.clone().equal(index.clone()).nonzero();
+            value = Term::ite(c, v.clone(), value);
+        }
+        value
+    }
+
+    /// Merge two arrays under a given condition, such that each
+    /// element of the result is that of the first array if the
+    /// condition holds, and that of the second otherwise.  Stores
+    /// common to both arrays are retained as is.
+    pub fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Self {
+        let n = lhs.stores.iter().zip(rhs.stores.iter()).take_while(|(l,r)| l == r).count();
+        let mut array = Self{stores: lhs.stores[..n].to_vec()};
+        for (i,_) in lhs.stores[n..].iter().chain(rhs.stores[n..].iter()) {
+            let v = Term::ite(cond.clone(), lhs.select(i), rhs.select(i));
+            array.store(i.clone(), v);
+        }
+        array
+    }
+}
+
+/// Determine whether two indices are known to be the same (i.e.
+/// `Some(true)`), known to be different (i.e. `Some(false)`), or
+/// neither.  Indices which differ only by a constant offset from the
+/// same term are known to be different.
+fn alias(lhs: &Term, rhs: &Term) -> Option<bool> {
+    if let Some(b) = lhs.clone().equal(rhs.clone()).to_bool() {
+        return Some(b);
+    }
+    let (l,i) = offset(lhs);
+    let (r,j) = offset(rhs);
+    if l == r && i != j { Some(false) } else { None }
+}
+
+/// Split a term into a base term and a constant offset.
+fn offset(term: &Term) -> (Term,BitVec) {
+    match term.kind() {
+        TermKind::Binary(BinaryOp::Add,t,c) if c.as_constant().is_some() => {
+            (t.clone(), c.as_constant().unwrap().clone())
+        }
+        _ => (term.clone(), BitVec::zero(term.width()))
+    }
+}
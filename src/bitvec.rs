@@ -0,0 +1,335 @@
+use std::cmp::Ordering;
+use std::fmt;
+use crate::{MachineWord};
+
+/// Number of bits held in each limb of a `BitVec`.
+const LIMB_BITS : usize = 64;
+
+/// A bitstring of arbitrary (but fixed) width, interpreted as an
+/// unsigned integer.  The width is chosen at construction and all
+/// arithmetic is performed modulo `2^width`.  This provides a
+/// concrete `MachineWord` for machines whose words do not correspond
+/// to a native integer type (e.g. 256bit words as found in the EVM).
+///
+/// Binary operations require both operands to have the same width,
+/// and will panic otherwise.  Division (and remainder) by zero
+/// produces zero.
+#[derive(Clone,PartialEq,Eq,Hash)]
+pub struct BitVec {
+    /// Number of bits in this bitvector.
+    width: usize,
+    /// Limbs in little-endian order.  Any bits in the last limb
+    /// beyond `width` are always zero.
+    limbs: Vec<u64>
+}
+
+impl BitVec {
+    /// Construct a bitvector of a given width where every bit is
+    /// zero.
+    pub fn zero(width: usize) -> Self {
+        assert!(width > 0, "bitvector width must be non-zero");
+        let limbs = vec![0; width.div_ceil(LIMB_BITS)];
+        Self{width,limbs}
+    }
+
+    /// Construct a bitvector of a given width where every bit is
+    /// one (i.e. the largest unsigned value of that width).
+    pub fn ones(width: usize) -> Self {
+        let mut r = Self::zero(width);
+        r.limbs.iter_mut().for_each(|l| *l = u64::MAX);
+        r.normalise();
+        r
+    }
+
+    /// Construct a bitvector of a given width from a `u64` value.
+    /// Any bits of `value` beyond the given width are discarded.
+    pub fn from_u64(width: usize, value: u64) -> Self {
+        let mut r = Self::zero(width);
+        r.limbs[0] = value;
+        r.normalise();
+        r
+    }
+
+    /// Construct a bitvector of a given width from a `u128` value.
+    /// Any bits of `value` beyond the given width are discarded.
+    pub fn from_u128(width: usize, value: u128) -> Self {
+        let mut r = Self::zero(width);
+        r.limbs[0] = value as u64;
+        if r.limbs.len() > 1 { r.limbs[1] = (value >> 64) as u64; }
+        r.normalise();
+        r
+    }
+
+    /// Get the width (in bits) of this bitvector.
+    pub fn width(&self) -> usize { self.width }
+
+    /// Get the `nth` bit of this bitvector (where `n==0` is the
+    /// least significant bit).
+    pub fn bit(&self, n: usize) -> bool {
+        assert!(n < self.width, "bit index out of bounds");
+        (self.limbs[n / LIMB_BITS] >> (n % LIMB_BITS)) & 1 == 1
+    }
+
+    /// Set the `nth` bit of this bitvector (where `n==0` is the least
+    /// significant bit).
+    pub fn set_bit(&mut self, n: usize, value: bool) {
+        assert!(n < self.width, "bit index out of bounds");
+        let mask = 1u64 << (n % LIMB_BITS);
+        if value {
+            self.limbs[n / LIMB_BITS] |= mask;
+        } else {
+            self.limbs[n / LIMB_BITS] &= !mask;
+        }
+    }
+
+    /// Check whether every bit of this bitvector is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|l| *l == 0)
+    }
+
+    /// Convert this bitvector into a `u64`, provided its value fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.limbs[1..].iter().all(|l| *l == 0) {
+            Some(self.limbs[0])
+        } else {
+            None
+        }
+    }
+
+    /// Convert this bitvector into a `u128`, provided its value
+    /// fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        let n = self.limbs.len().min(2);
+        if self.limbs[n..].iter().all(|l| *l == 0) {
+            let hi = if n > 1 { self.limbs[1] as u128 } else { 0 };
+            Some((hi << 64) | self.limbs[0] as u128)
+        } else {
+            None
+        }
+    }
+
+    /// Add two bitvectors, additionally reporting whether or not the
+    /// result wrapped around.
+    pub fn overflowing_add(&self, rhs: &Self) -> (Self,bool) {
+        self.check_width(rhs);
+        let mut r = Self::zero(self.width);
+        let mut carry = false;
+        for i in 0..self.limbs.len() {
+            let (s1,c1) = self.limbs[i].overflowing_add(rhs.limbs[i]);
+            let (s2,c2) = s1.overflowing_add(carry as u64);
+            r.limbs[i] = s2;
+            carry = c1 || c2;
+        }
+        // Overflow is either a carry out of the final limb, or bits
+        // set beyond the width within it.
+        let overflow = carry || r.excess_bits() != 0;
+        r.normalise();
+        (r,overflow)
+    }
+
+    /// Subtract two bitvectors, additionally reporting whether or
+    /// not the result wrapped around (i.e. `rhs > self`).
+    pub fn overflowing_sub(&self, rhs: &Self) -> (Self,bool) {
+        self.check_width(rhs);
+        let (r,_) = self.overflowing_add(&rhs.clone().neg());
+        (r, rhs > self)
+    }
+
+    /// Multiply two bitvectors, additionally reporting whether or not
+    /// the result wrapped around.
+    pub fn overflowing_mul(&self, rhs: &Self) -> (Self,bool) {
+        self.check_width(rhs);
+        let n = self.limbs.len();
+        // Compute full product
+        let mut full = vec![0u64; 2 * n];
+        for i in 0..n {
+            let mut carry : u128 = 0;
+            for j in 0..n {
+                let t = (self.limbs[i] as u128) * (rhs.limbs[j] as u128)
+                    + (full[i+j] as u128) + carry;
+                full[i+j] = t as u64;
+                carry = t >> 64;
+            }
+            full[i+n] = carry as u64;
+        }
+        // Truncate to width
+        let mut r = Self{width: self.width, limbs: full[..n].to_vec()};
+        let overflow = full[n..].iter().any(|l| *l != 0) || r.excess_bits() != 0;
+        r.normalise();
+        (r,overflow)
+    }
+
+    /// Compute both quotient and remainder of an unsigned division.
+    /// Division by zero produces zero for both.
+    pub fn div_rem(&self, rhs: &Self) -> (Self,Self) {
+        self.check_width(rhs);
+        let mut q = Self::zero(self.width);
+        let mut r = Self::zero(self.width);
+        if rhs.is_zero() { return (q,r); }
+        // Standard shift-subtract long division
+        for i in (0..self.width).rev() {
+            // Account for the bit shifted out of the remainder
+            let carry = r.bit(self.width - 1);
+            r.shl1();
+            r.set_bit(0, self.bit(i));
+            if carry || &r >= rhs {
+                r = r.overflowing_sub(rhs).0;
+                q.set_bit(i, true);
+            }
+        }
+        (q,r)
+    }
+
+    /// Count the number of leading (i.e. most significant) zero bits.
+    pub fn leading_zeros(&self) -> usize {
+        (0..self.width).rev().take_while(|i| !self.bit(*i)).count()
+    }
+
+    /// Count the number of trailing (i.e. least significant) zero
+    /// bits.
+    pub fn trailing_zeros(&self) -> usize {
+        (0..self.width).take_while(|i| !self.bit(*i)).count()
+    }
+
+    /// Shift all bits left by one position, discarding the most
+    /// significant bit.
+    fn shl1(&mut self) {
+        let mut carry = 0;
+        for l in self.limbs.iter_mut() {
+            let next = *l >> (LIMB_BITS - 1);
+            *l = (*l << 1) | carry;
+            carry = next;
+        }
+        self.normalise();
+    }
+
+    /// Bits in the last limb which lie beyond the width.
+    fn excess_bits(&self) -> u64 {
+        let n = self.width % LIMB_BITS;
+        if n == 0 { 0 } else { self.limbs[self.limbs.len()-1] & !((1u64 << n) - 1) }
+    }
+
+    /// Clear any bits in the last limb which lie beyond the width.
+    fn normalise(&mut self) {
+        let n = self.width % LIMB_BITS;
+        if n != 0 {
+            let last = self.limbs.len() - 1;
+            self.limbs[last] &= (1u64 << n) - 1;
+        }
+    }
+
+    fn check_width(&self, rhs: &Self) {
+        assert_eq!(self.width, rhs.width, "bitvector width mismatch");
+    }
+
+    fn bool(width: usize, b: bool) -> Self {
+        Self::from_u64(width, b as u64)
+    }
+
+    fn map2<F:Fn(u64,u64)->u64>(mut self, rhs: &Self, f: F) -> Self {
+        self.check_width(rhs);
+        for (l,r) in self.limbs.iter_mut().zip(rhs.limbs.iter()) {
+            *l = f(*l,*r);
+        }
+        self.normalise();
+        self
+    }
+}
+
+impl PartialOrd for BitVec {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Bitvectors of the same width are ordered as unsigned integers.
+/// Bitvectors of different widths are ordered by width.
+impl Ord for BitVec {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.width.cmp(&other.width)
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self, self.width)
+    }
+}
+
+/// Bitvectors are displayed as hexadecimal literals (e.g. `0x1f`).
+impl fmt::Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut i = self.limbs.len() - 1;
+        // Skip leading zero limbs
+        while i > 0 && self.limbs[i] == 0 { i -= 1; }
+        write!(f, "0x{:x}", self.limbs[i])?;
+        for l in self.limbs[..i].iter().rev() {
+            write!(f, "{:016x}", l)?;
+        }
+        Ok(())
+    }
+}
+
+impl MachineWord for BitVec {
+    // Comparators
+    fn less_than(self,rhs:Self)->Self {
+        self.check_width(&rhs);
+        let w = self.width;
+        Self::bool(w, self < rhs)
+    }
+    fn equal(self,rhs:Self)->Self {
+        self.check_width(&rhs);
+        let w = self.width;
+        Self::bool(w, self == rhs)
+    }
+    // Arithmetic
+    fn add(self,rhs:Self)->Self {
+        self.overflowing_add(&rhs).0
+    }
+    fn sub(self,rhs:Self)->Self {
+        self.overflowing_sub(&rhs).0
+    }
+    fn mul(self,rhs:Self)->Self {
+        self.overflowing_mul(&rhs).0
+    }
+    fn div(self,rhs:Self)->Self {
+        self.div_rem(&rhs).0
+    }
+    fn rem(self,rhs:Self)->Self {
+        self.div_rem(&rhs).1
+    }
+    fn neg(self)->Self {
+        let one = Self::from_u64(self.width, 1);
+        self.not().add(one)
+    }
+    // Bitwise
+    fn and(self,rhs:Self)->Self {
+        self.map2(&rhs, |l,r| l & r)
+    }
+    fn or(self,rhs:Self)->Self {
+        self.map2(&rhs, |l,r| l | r)
+    }
+    fn xor(self,rhs:Self)->Self {
+        self.map2(&rhs, |l,r| l ^ r)
+    }
+    fn not(mut self)->Self {
+        self.limbs.iter_mut().for_each(|l| *l = !*l);
+        self.normalise();
+        self
+    }
+    // Truthiness
+    fn to_bool(&self)->Option<bool> {
+        Some(!self.is_zero())
+    }
+    // Conversions
+    fn constant_like(&self, value: u64)->Self {
+        Self::from_u64(self.width, value)
+    }
+    fn to_usize(&self)->Option<usize> {
+        self.to_u64().and_then(|v| usize::try_from(v).ok())
+    }
+    fn to_bitvec(&self)->Option<BitVec> {
+        Some(self.clone())
+    }
+}
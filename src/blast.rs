@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap,HashMap};
+use crate::{BinaryOp,BitVec,Lit,Model,Response,Solver,Term,TermKind,UnaryOp};
+
+/// A bit-blaster translates terms into propositional clauses, such
+/// that each bit of a term is represented by a literal.  Terms are
+/// translated once only (i.e. sharing is preserved), and gates over
+/// constant (or identical) literals are simplified away.
+pub struct BitBlaster {
+    solver: Solver,
+    /// Literal which always holds.
+    truth: Lit,
+    /// Translations of terms (keyed by identifier), least significant
+    /// bit first.
+    cache: HashMap<usize,Vec<Lit>>,
+    /// Translations of variables (keyed by name and width).
+    vars: BTreeMap<(String,usize),Vec<Lit>>
+}
+
+impl BitBlaster {
+    pub fn new() -> Self {
+        let mut solver = Solver::new();
+        let truth = solver.new_var();
+        solver.add_clause(&[truth]);
+        Self{solver, truth, cache: HashMap::new(), vars: BTreeMap::new()}
+    }
+
+    /// Assert that a given term holds (i.e. is non-zero).
+    pub fn assert(&mut self, t: &Term) {
+        let bits = self.blast(t);
+        self.solver.add_clause(&bits);
+    }
+
+    /// Determine whether the assertions made so far are satisfiable
+    /// and, if so, return a satisfying assignment for all variables.
+    pub fn solve(&mut self) -> Option<Model> {
+        if !self.solver.solve() { return None; }
+        let mut model = Model::new();
+        for ((name,_),bits) in &self.vars {
+            let mut v = BitVec::zero(bits.len());
+            for (i,b) in bits.iter().enumerate() {
+                v.set_bit(i, self.solver.model(b.var()) == b.is_positive());
+            }
+            model.insert(name, v);
+        }
+        Some(model)
+    }
+
+    /// Translate a given term into one literal for each of its bits,
+    /// least significant first.
+    pub fn blast(&mut self, t: &Term) -> Vec<Lit> {
+        if let Some(bits) = self.cache.get(&t.id()) {
+            return bits.clone();
+        }
+        let w = t.width();
+        let bits = match t.kind() {
+            TermKind::Var(n) => {
+                let bits : Vec<Lit> = (0..w).map(|_| self.solver.new_var()).collect();
+                self.vars.insert((n.clone(),w), bits.clone());
+                bits
+            }
+            TermKind::Const(v) => (0..w).map(|i| self.constant(v.bit(i))).collect(),
+            TermKind::Unary(op,a) => {
+                let a = self.blast(a);
+                match op {
+                    UnaryOp::Neg => {
+                        let not_a : Vec<Lit> = a.iter().map(|&l| !l).collect();
+                        self.add(&not_a, &self.word(w,0), self.truth).0
+                    }
+                    UnaryOp::Not => a.iter().map(|&l| !l).collect(),
+                    UnaryOp::NonZero => vec![self.or_all(&a)]
+                }
+            }
+            TermKind::Binary(op,l,r) => {
+                let l = self.blast(l);
+                let r = self.blast(r);
+                match op {
+                    BinaryOp::LessThan => {
+                        let b = self.less_than(&l,&r);
+                        self.extend(b, w)
+                    }
+                    BinaryOp::Equal => {
+                        let b = self.equal(&l,&r);
+                        self.extend(b, w)
+                    }
+                    BinaryOp::Add => self.add(&l, &r, !self.truth).0,
+                    BinaryOp::Mul => self.mul(&l,&r),
+                    BinaryOp::Div => self.div_rem(&l,&r).0,
+                    BinaryOp::Rem => self.div_rem(&l,&r).1,
+                    BinaryOp::And => self.zip(&l, &r, Self::and),
+                    BinaryOp::Or => self.zip(&l, &r, Self::or),
+                    BinaryOp::Xor => self.zip(&l, &r, Self::xor)
+                }
+            }
+            TermKind::Ite(c,l,r) => {
+                let c = self.blast(c)[0];
+                let l = self.blast(l);
+                let r = self.blast(r);
+                self.mux_word(c, &l, &r)
+            }
+        };
+        self.cache.insert(t.id(), bits.clone());
+        bits
+    }
+
+    // ===============================================================
+    // Gates
+    // ===============================================================
+
+    fn constant(&self, b: bool) -> Lit {
+        if b { self.truth } else { !self.truth }
+    }
+
+    fn and(&mut self, a: Lit, b: Lit) -> Lit {
+        let f = !self.truth;
+        if a == f || b == f || a == !b { return f; }
+        if a == self.truth || a == b { return b; }
+        if b == self.truth { return a; }
+        let o = self.solver.new_var();
+        self.solver.add_clause(&[!o, a]);
+        self.solver.add_clause(&[!o, b]);
+        self.solver.add_clause(&[o, !a, !b]);
+        o
+    }
+
+    fn or(&mut self, a: Lit, b: Lit) -> Lit {
+        !self.and(!a, !b)
+    }
+
+    fn xor(&mut self, a: Lit, b: Lit) -> Lit {
+        if a == !self.truth { return b; }
+        if b == !self.truth { return a; }
+        if a == self.truth { return !b; }
+        if b == self.truth { return !a; }
+        if a == b { return !self.truth; }
+        if a == !b { return self.truth; }
+        let o = self.solver.new_var();
+        self.solver.add_clause(&[!o, a, b]);
+        self.solver.add_clause(&[!o, !a, !b]);
+        self.solver.add_clause(&[o, !a, b]);
+        self.solver.add_clause(&[o, a, !b]);
+        o
+    }
+
+    /// Select `a` when `c` holds, and `b` otherwise.
+    fn mux(&mut self, c: Lit, a: Lit, b: Lit) -> Lit {
+        if a == b { return a; }
+        let x = self.and(c, a);
+        let y = self.and(!c, b);
+        self.or(x, y)
+    }
+
+    fn or_all(&mut self, bits: &[Lit]) -> Lit {
+        bits.iter().fold(!self.truth, |acc,&b| self.or(acc,b))
+    }
+
+    // ===============================================================
+    // Words
+    // ===============================================================
+
+    fn word(&self, width: usize, value: u64) -> Vec<Lit> {
+        let v = BitVec::from_u64(width, value);
+        (0..width).map(|i| self.constant(v.bit(i))).collect()
+    }
+
+    /// Zero extend a single bit to a given width.
+    fn extend(&self, bit: Lit, width: usize) -> Vec<Lit> {
+        let mut bits = vec![!self.truth; width];
+        bits[0] = bit;
+        bits
+    }
+
+    fn zip<F:Fn(&mut Self,Lit,Lit)->Lit>(&mut self, l: &[Lit], r: &[Lit], f: F) -> Vec<Lit> {
+        l.iter().zip(r.iter()).map(|(&a,&b)| f(self,a,b)).collect()
+    }
+
+    fn mux_word(&mut self, c: Lit, l: &[Lit], r: &[Lit]) -> Vec<Lit> {
+        self.zip(l, r, |s,a,b| s.mux(c,a,b))
+    }
+
+    /// Ripple-carry addition, returning the sum and the carry out.
+    fn add(&mut self, l: &[Lit], r: &[Lit], mut carry: Lit) -> (Vec<Lit>,Lit) {
+        let mut sum = Vec::with_capacity(l.len());
+        for (&a,&b) in l.iter().zip(r.iter()) {
+            let t = self.xor(a,b);
+            sum.push(self.xor(t,carry));
+            let x = self.and(a,b);
+            let y = self.and(t,carry);
+            carry = self.or(x,y);
+        }
+        (sum,carry)
+    }
+
+    /// Shift-and-add multiplication (modulo the width).
+    fn mul(&mut self, l: &[Lit], r: &[Lit]) -> Vec<Lit> {
+        let w = l.len();
+        let mut acc = self.word(w,0);
+        for (i,&b) in r.iter().enumerate() {
+            let mut partial = vec![!self.truth; i];
+            partial.extend(l[..w-i].iter().map(|&a| self.and(a,b)));
+            acc = self.add(&acc, &partial, !self.truth).0;
+        }
+        acc
+    }
+
+    /// Unsigned comparison `l < r`.
+    fn less_than(&mut self, l: &[Lit], r: &[Lit]) -> Lit {
+        // l < r iff l - r borrows (i.e. l + !r + 1 does not carry)
+        let not_r : Vec<Lit> = r.iter().map(|&b| !b).collect();
+        !self.add(l, &not_r, self.truth).1
+    }
+
+    fn equal(&mut self, l: &[Lit], r: &[Lit]) -> Lit {
+        let diff = self.zip(l, r, Self::xor);
+        !self.or_all(&diff)
+    }
+
+    /// Restoring division, returning the quotient and remainder.  As
+    /// for all words, division by zero gives zero (as does the
+    /// remainder).
+    fn div_rem(&mut self, l: &[Lit], r: &[Lit]) -> (Vec<Lit>,Vec<Lit>) {
+        let w = l.len();
+        let f = !self.truth;
+        // Divisor and partial remainder use an extra bit, so the
+        // latter cannot overflow when shifted.
+        let mut divisor = r.to_vec();
+        divisor.push(f);
+        let not_divisor : Vec<Lit> = divisor.iter().map(|&b| !b).collect();
+        let mut rem = vec![f; w+1];
+        let mut quot = vec![f; w];
+        for i in (0..w).rev() {
+            rem.pop();
+            rem.insert(0, l[i]);
+            // Subtract if rem >= divisor (i.e. subtraction carries)
+            let (diff,carry) = self.add(&rem, &not_divisor, self.truth);
+            rem = self.mux_word(carry, &diff, &rem);
+            quot[i] = carry;
+        }
+        rem.pop();
+        let zero = self.equal(r, &self.word(w,0));
+        let z = self.word(w,0);
+        (self.mux_word(zero, &z, &quot), self.mux_word(zero, &z, &rem))
+    }
+}
+
+impl Default for BitBlaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check the validity of a given verification condition by
+/// bit-blasting, without requiring an external solver.  The response
+/// is that for the query constructed by `validity_query()`: `Unsat`
+/// indicates the condition is valid and, otherwise, the model is a
+/// counterexample.
+pub fn check_validity(vc: &Term) -> Response {
+    let mut blaster = BitBlaster::new();
+    // Assert that every bit of the condition is zero
+    for b in blaster.blast(vc) {
+        blaster.solver.add_clause(&[!b]);
+    }
+    match blaster.solve() {
+        Some(model) => Response::Sat(model),
+        None => Response::Unsat
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+use crate::{Machine,MachineState,StepMachine,Term,Verifiable};
+
+/// An error raised by a machine, along with the context in which it
+/// arose.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ContextError<I,E> {
+    /// Position of the failing instruction.
+    pub pc: usize,
+    /// The failing instruction, or `None` if the position was itself
+    /// invalid.
+    pub instruction: Option<I>,
+    /// Size of the stack before the failing instruction executed, or
+    /// `None` if no state was involved (i.e. for `get()`).
+    pub depth: Option<usize>,
+    /// The underlying error.
+    pub error: E
+}
+
+impl<I:fmt::Debug,E:fmt::Display> fmt::Display for ContextError<I,E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at pc {}", self.error, self.pc)?;
+        if let Some(insn) = &self.instruction {
+            write!(f, " ({:?})", insn)?;
+        }
+        if let Some(depth) = self.depth {
+            write!(f, " with stack depth {}", depth)?;
+        }
+        Ok(())
+    }
+}
+
+impl<I,E> std::error::Error for ContextError<I,E>
+where I:fmt::Debug, E:std::error::Error+'static {
+    fn source(&self) -> Option<&(dyn std::error::Error+'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A machine which executes a given machine, but whose errors record
+/// the context in which they arose.  This is particularly useful for
+/// errors arising from long-running exploration, where the state
+/// itself is not retained.
+pub struct ContextMachine<'a,M> {
+    machine: &'a M
+}
+
+impl<'a,M> ContextMachine<'a,M> {
+    pub fn new(machine: &'a M) -> Self {
+        Self{machine}
+    }
+}
+
+impl<'a,M:Machine> Machine for ContextMachine<'a,M>
+where M::Instruction: Clone {
+    type State = M::State;
+    type Error = ContextError<M::Instruction,M::Error>;
+    type Outcome = M::Outcome;
+    type Instruction = M::Instruction;
+
+    fn get(&self, pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.machine.get(pc).map_err(|error| {
+            ContextError{pc, instruction: None, depth: None, error}
+        })
+    }
+
+    fn execute(&self, state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        let (pc,depth) = (state.pc(),Some(state.size()));
+        self.machine.execute(state).map_err(|error| {
+            let instruction = self.machine.get(pc).ok().cloned();
+            ContextError{pc, instruction, depth, error}
+        })
+    }
+}
+
+impl<'a,M:StepMachine> StepMachine for ContextMachine<'a,M>
+where M::Instruction: Clone {
+    type Value = M::Value;
+}
+
+impl<'a,M:Verifiable> Verifiable for ContextMachine<'a,M>
+where M::Instruction: Clone {
+    fn obligation(&self, state: &Self::State) -> Option<Term> {
+        self.machine.obligation(state)
+    }
+
+    fn accesses(&self, state: &Self::State) -> bool {
+        self.machine.accesses(state)
+    }
+
+    fn guard(&self, state: &Self::State, successor: &Self::State) -> Term {
+        self.machine.guard(state, successor)
+    }
+}
@@ -1,7 +1,8 @@
+use std::fmt;
 use crate::{MachineError};
 
-/// A minimal implementation of `MachineError` which contains only two
-/// error kinds.
+/// A minimal implementation of `MachineError` which distinguishes
+/// each error kind, but records no further context.
 #[derive(Clone,Copy,Debug,PartialEq)]
 pub enum MinimalMachineError {
     /// Indicates an attempt was made to read an instruction that does
@@ -9,7 +10,35 @@ pub enum MinimalMachineError {
     InvalidPC,
     /// Indicates an attempt was made to pop an item from an empty
     /// stack.
-    StackUnderflow
+    StackUnderflow,
+    /// Indicates an attempt was made to push an item onto a full
+    /// stack.
+    StackOverflow,
+    /// Indicates an attempt was made to divide by zero.
+    DivisionByZero,
+    /// Indicates an invalid (or unknown) opcode was encountered.
+    InvalidOpcode,
+    /// Indicates an invalid jump destination was encountered.
+    InvalidJump,
+    /// Indicates that execution exhausted its available gas.
+    OutOfGas,
+    /// Indicates that an assertion did not hold.
+    AssertionFailure,
+    /// Indicates an invalid (or unknown) memory (or storage) address
+    /// was accessed.
+    InvalidAddress,
+    /// Indicates a value which is not known was stored where a known
+    /// value is required.
+    UnknownValue,
+    /// Indicates an attempt was made to call a subroutine when the
+    /// maximum call depth had been reached.
+    CallDepthExceeded,
+    /// Indicates an attempt was made to access a local slot which
+    /// does not exist.
+    InvalidLocal,
+    /// Indicates an attempt was made to return from a subroutine when
+    /// the call stack was empty.
+    InvalidReturn
 }
 
 impl MachineError for MinimalMachineError {
@@ -20,5 +49,190 @@ impl MachineError for MinimalMachineError {
     fn stack_underflow() -> Self {
         MinimalMachineError::StackUnderflow
     }
-    
+
+    fn stack_overflow() -> Self {
+        MinimalMachineError::StackOverflow
+    }
+
+    fn division_by_zero() -> Self {
+        MinimalMachineError::DivisionByZero
+    }
+
+    fn invalid_opcode(_: u8) -> Self {
+        MinimalMachineError::InvalidOpcode
+    }
+
+    fn invalid_jump(_: usize) -> Self {
+        MinimalMachineError::InvalidJump
+    }
+
+    fn out_of_gas() -> Self {
+        MinimalMachineError::OutOfGas
+    }
+
+    fn assertion_failure() -> Self {
+        MinimalMachineError::AssertionFailure
+    }
+
+    fn invalid_address() -> Self {
+        MinimalMachineError::InvalidAddress
+    }
+
+    fn unknown_value() -> Self {
+        MinimalMachineError::UnknownValue
+    }
+
+    fn call_depth_exceeded() -> Self {
+        MinimalMachineError::CallDepthExceeded
+    }
+
+    fn invalid_local(_: usize) -> Self {
+        MinimalMachineError::InvalidLocal
+    }
+
+    fn invalid_return() -> Self {
+        MinimalMachineError::InvalidReturn
+    }
+}
+
+impl fmt::Display for MinimalMachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MinimalMachineError::InvalidPC => write!(f, "invalid pc"),
+            MinimalMachineError::StackUnderflow => write!(f, "stack underflow"),
+            MinimalMachineError::StackOverflow => write!(f, "stack overflow"),
+            MinimalMachineError::DivisionByZero => write!(f, "division by zero"),
+            MinimalMachineError::InvalidOpcode => write!(f, "invalid opcode"),
+            MinimalMachineError::InvalidJump => write!(f, "invalid jump destination"),
+            MinimalMachineError::OutOfGas => write!(f, "out of gas"),
+            MinimalMachineError::AssertionFailure => write!(f, "assertion failure"),
+            MinimalMachineError::InvalidAddress => write!(f, "invalid memory address"),
+            MinimalMachineError::UnknownValue => write!(f, "unknown value"),
+            MinimalMachineError::CallDepthExceeded => write!(f, "call depth exceeded"),
+            MinimalMachineError::InvalidLocal => write!(f, "invalid local slot"),
+            MinimalMachineError::InvalidReturn => write!(f, "return without call")
+        }
+    }
+}
+
+impl std::error::Error for MinimalMachineError {
+
+}
+
+/// A fuller implementation of `MachineError` which additionally
+/// records the context of each fault (where available), and can be
+/// reported to users.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum StandardMachineError {
+    /// Indicates an attempt was made to read an instruction that does
+    /// not exist (e.g. is beyond the end of the code section).
+    InvalidPC,
+    /// Indicates an attempt was made to pop an item from an empty
+    /// stack.
+    StackUnderflow,
+    /// Indicates an attempt was made to push an item onto a full
+    /// stack.
+    StackOverflow,
+    /// Indicates an attempt was made to divide by zero.
+    DivisionByZero,
+    /// Indicates a given opcode is invalid (or unknown).
+    InvalidOpcode(u8),
+    /// Indicates a given position is not a valid jump destination.
+    InvalidJump(usize),
+    /// Indicates that execution exhausted its available gas.
+    OutOfGas,
+    /// Indicates that an assertion did not hold.
+    AssertionFailure,
+    /// Indicates an invalid (or unknown) memory (or storage) address
+    /// was accessed.
+    InvalidAddress,
+    /// Indicates a value which is not known was stored where a known
+    /// value is required.
+    UnknownValue,
+    /// Indicates an attempt was made to call a subroutine when the
+    /// maximum call depth had been reached.
+    CallDepthExceeded,
+    /// Indicates a given local slot does not exist.
+    InvalidLocal(usize),
+    /// Indicates an attempt was made to return from a subroutine when
+    /// the call stack was empty.
+    InvalidReturn
+}
+
+impl MachineError for StandardMachineError {
+    fn invalid_pc() -> Self {
+        StandardMachineError::InvalidPC
+    }
+
+    fn stack_underflow() -> Self {
+        StandardMachineError::StackUnderflow
+    }
+
+    fn stack_overflow() -> Self {
+        StandardMachineError::StackOverflow
+    }
+
+    fn division_by_zero() -> Self {
+        StandardMachineError::DivisionByZero
+    }
+
+    fn invalid_opcode(opcode: u8) -> Self {
+        StandardMachineError::InvalidOpcode(opcode)
+    }
+
+    fn invalid_jump(target: usize) -> Self {
+        StandardMachineError::InvalidJump(target)
+    }
+
+    fn out_of_gas() -> Self {
+        StandardMachineError::OutOfGas
+    }
+
+    fn assertion_failure() -> Self {
+        StandardMachineError::AssertionFailure
+    }
+
+    fn invalid_address() -> Self {
+        StandardMachineError::InvalidAddress
+    }
+
+    fn unknown_value() -> Self {
+        StandardMachineError::UnknownValue
+    }
+
+    fn call_depth_exceeded() -> Self {
+        StandardMachineError::CallDepthExceeded
+    }
+
+    fn invalid_local(slot: usize) -> Self {
+        StandardMachineError::InvalidLocal(slot)
+    }
+
+    fn invalid_return() -> Self {
+        StandardMachineError::InvalidReturn
+    }
+}
+
+impl fmt::Display for StandardMachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StandardMachineError::InvalidPC => write!(f, "invalid pc"),
+            StandardMachineError::StackUnderflow => write!(f, "stack underflow"),
+            StandardMachineError::StackOverflow => write!(f, "stack overflow"),
+            StandardMachineError::DivisionByZero => write!(f, "division by zero"),
+            StandardMachineError::InvalidOpcode(op) => write!(f, "invalid opcode 0x{:02x}", op),
+            StandardMachineError::InvalidJump(target) => write!(f, "invalid jump destination {}", target),
+            StandardMachineError::OutOfGas => write!(f, "out of gas"),
+            StandardMachineError::AssertionFailure => write!(f, "assertion failure"),
+            StandardMachineError::InvalidAddress => write!(f, "invalid memory address"),
+            StandardMachineError::UnknownValue => write!(f, "unknown value"),
+            StandardMachineError::CallDepthExceeded => write!(f, "call depth exceeded"),
+            StandardMachineError::InvalidLocal(slot) => write!(f, "invalid local slot {}", slot),
+            StandardMachineError::InvalidReturn => write!(f, "return without call")
+        }
+    }
+}
+
+impl std::error::Error for StandardMachineError {
+
 }
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use crate::{Machine};
+
+// ===================================================================
+// Branching
+// ===================================================================
+
+/// Determines how the outcome of executing a given state should be
+/// interpreted during exploration.  Specifically, an outcome either
+/// continues execution with zero (or more) states, or terminates the
+/// current path (in some sense).
+pub trait Branching<S> {
+    /// Represents the result of a path which has terminated.
+    type Terminal;
+
+    /// Determine whether this outcome continues or terminates the
+    /// current path.
+    fn branch(self) -> Branch<S,Self::Terminal>;
+}
+
+/// The interpretation of an outcome during exploration.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Branch<S,T> {
+    /// Execution continues with zero (or more) states.  When no
+    /// states are given, the path is simply discarded (e.g. because
+    /// it is infeasible).
+    Continue(Vec<S>),
+    /// Execution terminates producing a given result.
+    Terminate(T)
+}
+
+// ===================================================================
+// Exploration
+// ===================================================================
+
+/// Determines the order in which states are explored.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Strategy {
+    /// Explore the most recently discovered states first.
+    DepthFirst,
+    /// Explore the least recently discovered states first.
+    BreadthFirst,
+    /// Explore depth-first, but do not execute states reached after
+    /// a given number of steps.  Such states are reported as
+    /// unexplored.
+    BoundedDepth(usize)
+}
+
+/// Identifies a limit which caused exploration to stop early.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Limit {
+    /// The maximum number of steps (i.e. calls to `execute()`) was
+    /// reached.
+    Steps,
+    /// The maximum number of states pending exploration was
+    /// exceeded.
+    States,
+    /// The maximum number of completed paths was reached.
+    Paths
+}
+
+/// A completed path through the machine.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Path<T,E> {
+    /// Number of steps taken along this path.
+    pub depth: usize,
+    /// Result of this path, which is either a terminal outcome or
+    /// an error.
+    pub result: Result<T,E>
+}
+
+/// The result of exploring a machine from some initial state.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Exploration<S,T,E> {
+    /// Every completed path, in the order they completed.
+    pub paths: Vec<Path<T,E>>,
+    /// States which were discovered but not explored, either
+    /// because they exceed the depth bound or because a limit was
+    /// reached.
+    pub unexplored: Vec<S>,
+    /// Number of steps (i.e. calls to `execute()`) taken.
+    pub steps: usize,
+    /// Number of states discovered (including the initial state).
+    pub states: usize,
+    /// The limit (if any) which stopped exploration early.
+    pub limit: Option<Limit>
+}
+
+/// The result of a terminated path for a given machine.
+pub(crate) type Terminal<M> = <<M as Machine>::Outcome as Branching<<M as Machine>::State>>::Terminal;
+
+/// Explores the states reachable from some initial state of a given
+/// machine by repeatedly executing them until every path has
+/// terminated (or some limit is reached).
+pub struct Explorer<'a,M:Machine> {
+    machine: &'a M,
+    strategy: Strategy,
+    max_steps: Option<usize>,
+    max_states: Option<usize>,
+    max_paths: Option<usize>
+}
+
+impl<'a,M:Machine> Explorer<'a,M>
+where M::Outcome: Branching<M::State> {
+    /// Construct an explorer for a given machine, which performs an
+    /// unbounded depth-first search.
+    pub fn new(machine: &'a M) -> Self {
+        Self{machine, strategy: Strategy::DepthFirst, max_steps: None, max_states: None, max_paths: None}
+    }
+
+    /// Set the search strategy used by this explorer.
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the maximum number of steps (i.e. calls to `execute()`)
+    /// which can be taken.
+    pub fn max_steps(mut self, n: usize) -> Self {
+        self.max_steps = Some(n);
+        self
+    }
+
+    /// Set the maximum number of states which can be pending
+    /// exploration at any one time.
+    pub fn max_states(mut self, n: usize) -> Self {
+        self.max_states = Some(n);
+        self
+    }
+
+    /// Set the maximum number of paths which can be completed.
+    pub fn max_paths(mut self, n: usize) -> Self {
+        self.max_paths = Some(n);
+        self
+    }
+
+    /// Explore all states reachable from a given initial state.
+    pub fn run(&self, init: M::State) -> Exploration<M::State,Terminal<M>,M::Error> {
+        let mut worklist = VecDeque::from([(init,0)]);
+        let mut r = Exploration{paths: Vec::new(), unexplored: Vec::new(), steps: 0, states: 1, limit: None};
+        //
+        while let Some((state,depth)) = self.next(&mut worklist) {
+            if let Some(limit) = self.check_limits(&r) {
+                r.limit = Some(limit);
+                r.unexplored.push(state);
+                break;
+            } else if matches!(self.strategy, Strategy::BoundedDepth(n) if depth >= n) {
+                r.unexplored.push(state);
+                continue;
+            }
+            r.steps += 1;
+            match self.machine.execute(state).map(Branching::branch) {
+                Ok(Branch::Continue(states)) => {
+                    r.states += states.len();
+                    self.add(&mut worklist, states, depth + 1);
+                    if self.max_states.is_some_and(|n| worklist.len() > n) {
+                        r.limit = Some(Limit::States);
+                        break;
+                    }
+                }
+                Ok(Branch::Terminate(t)) => {
+                    r.paths.push(Path{depth: depth + 1, result: Ok(t)});
+                }
+                Err(e) => {
+                    r.paths.push(Path{depth: depth + 1, result: Err(e)});
+                }
+            }
+        }
+        // Any remaining states were not explored
+        r.unexplored.extend(worklist.into_iter().map(|(s,_)| s));
+        r
+    }
+
+    fn next(&self, worklist: &mut VecDeque<(M::State,usize)>) -> Option<(M::State,usize)> {
+        match self.strategy {
+            Strategy::BreadthFirst => worklist.pop_front(),
+            _ => worklist.pop_back()
+        }
+    }
+
+    fn add(&self, worklist: &mut VecDeque<(M::State,usize)>, states: Vec<M::State>, depth: usize) {
+        match self.strategy {
+            Strategy::BreadthFirst => {
+                worklist.extend(states.into_iter().map(|s| (s,depth)));
+            }
+            _ => {
+                // Push in reverse order, so that the first successor
+                // is explored first.
+                worklist.extend(states.into_iter().rev().map(|s| (s,depth)));
+            }
+        }
+    }
+
+    fn check_limits<S,T,E>(&self, r: &Exploration<S,T,E>) -> Option<Limit> {
+        if self.max_steps.is_some_and(|n| r.steps >= n) {
+            Some(Limit::Steps)
+        } else if self.max_paths.is_some_and(|n| r.paths.len() >= n) {
+            Some(Limit::Paths)
+        } else {
+            None
+        }
+    }
+}
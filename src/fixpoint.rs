@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap,BTreeSet};
+use crate::{Branch,Branching,Lattice,Machine,MachineState};
+use crate::explore::Terminal;
+
+/// The result of an abstract interpretation of a machine.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Analysis<S,T,E> {
+    /// The stable (abstract) state before each reachable instruction,
+    /// keyed by `pc`.
+    pub states: BTreeMap<usize,S>,
+    /// Terminal outcomes arising from the stable state at a given
+    /// `pc`.
+    pub outcomes: BTreeMap<usize,T>,
+    /// Errors arising from the stable state at a given `pc`.
+    pub errors: BTreeMap<usize,E>,
+    /// Positions where states of different shapes (e.g. stack sizes)
+    /// met.  Such states cannot be joined and, hence, the analysis
+    /// does not propagate them.
+    pub inconsistent: BTreeSet<usize>,
+    /// Number of instructions executed.
+    pub iterations: usize,
+    /// Indicates whether or not a fixpoint was reached (i.e. the
+    /// analysis was not stopped early).
+    pub stable: bool
+}
+
+/// An abstract interpreter which maintains exactly one (joined)
+/// abstract state for each position within the instruction sequence,
+/// and iterates until a fixpoint is reached.  To ensure termination
+/// over lattices of large (or infinite) height, states are widened
+/// (rather than joined) once a given position has been updated more
+/// than a given number of times.
+pub struct Fixpoint<'a,M:Machine> {
+    machine: &'a M,
+    widening_delay: usize,
+    max_iterations: Option<usize>
+}
+
+impl<'a,M:Machine> Fixpoint<'a,M>
+where M::State: Lattice+Clone, M::Outcome: Branching<M::State> {
+    /// Construct a fixpoint solver for a given machine, which widens
+    /// immediately.
+    pub fn new(machine: &'a M) -> Self {
+        Self{machine, widening_delay: 0, max_iterations: None}
+    }
+
+    /// Set the number of times the state at a given position can be
+    /// updated using `join()`, before `widen()` is used instead.
+    pub fn widening_delay(mut self, n: usize) -> Self {
+        self.widening_delay = n;
+        self
+    }
+
+    /// Set the maximum number of instructions which can be executed
+    /// before the analysis is stopped.
+    pub fn max_iterations(mut self, n: usize) -> Self {
+        self.max_iterations = Some(n);
+        self
+    }
+
+    /// Analyse the machine from a given initial state.
+    pub fn run(&self, init: M::State) -> Analysis<M::State,Terminal<M>,M::Error> {
+        let mut r = Analysis{states: BTreeMap::new(), outcomes: BTreeMap::new(), errors: BTreeMap::new(),
+                             inconsistent: BTreeSet::new(), iterations: 0, stable: true};
+        let mut updates = BTreeMap::new();
+        // Process lowest positions first, so that loop bodies are
+        // (typically) stabilised before their exits.
+        let mut worklist = BTreeSet::from([init.pc()]);
+        r.states.insert(init.pc(), init);
+        //
+        while let Some(pc) = worklist.pop_first() {
+            if self.max_iterations.is_some_and(|n| r.iterations >= n) {
+                r.stable = false;
+                break;
+            }
+            r.iterations += 1;
+            r.outcomes.remove(&pc);
+            r.errors.remove(&pc);
+            let state = r.states[&pc].clone();
+            match self.machine.execute(state).map(Branching::branch) {
+                Ok(Branch::Continue(states)) => {
+                    for s in states {
+                        if let Some(n) = self.update(&mut r, &mut updates, s) {
+                            worklist.insert(n);
+                        }
+                    }
+                }
+                Ok(Branch::Terminate(t)) => { r.outcomes.insert(pc, t); }
+                Err(e) => { r.errors.insert(pc, e); }
+            }
+        }
+        r
+    }
+
+    /// Merge a given state into that currently held for its position,
+    /// returning that position if the held state changed.
+    fn update<T,E>(&self, r: &mut Analysis<M::State,T,E>, updates: &mut BTreeMap<usize,usize>, state: M::State) -> Option<usize> {
+        let pc = state.pc();
+        let next = match r.states.get(&pc) {
+            None => state,
+            Some(old) if !old.compatible(&state) => {
+                r.inconsistent.insert(pc);
+                return None;
+            }
+            Some(old) if state.leq(old) => {
+                return None;
+            }
+            Some(old) => {
+                let count = updates.entry(pc).or_insert(0);
+                *count += 1;
+                let joined = old.join(&state);
+                if *count > self.widening_delay { old.widen(&joined) } else { joined }
+            }
+        };
+        r.states.insert(pc, next);
+        Some(pc)
+    }
+}
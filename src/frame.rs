@@ -0,0 +1,63 @@
+use crate::{MachineState};
+
+/// The default maximum number of frames on a call stack.
+pub const DEFAULT_MAX_CALLS : usize = 1024;
+
+/// A frame on the call stack, which records the position at which
+/// execution continues when the subroutine returns, along with any
+/// local slots of the subroutine.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Frame<T> {
+    return_pc: usize,
+    locals: Vec<T>
+}
+
+impl<T> Frame<T> {
+    pub fn new(return_pc: usize, locals: Vec<T>) -> Self {
+        Self{return_pc, locals}
+    }
+
+    /// Get the position at which execution continues on return.
+    pub fn return_pc(&self) -> usize { self.return_pc }
+
+    /// Get the local slots of this frame.
+    pub fn locals(&self) -> &[T] { &self.locals }
+
+    /// Get the `nth` local slot, if it exists.
+    pub fn get(&self, n: usize) -> Option<&T> {
+        self.locals.get(n)
+    }
+
+    /// Set the `nth` local slot, whilst returning the item previously
+    /// held there (or `None` if it does not exist).
+    pub fn set(&mut self, n: usize, item: T) -> Option<T> {
+        self.locals.get_mut(n).map(|l| std::mem::replace(l, item))
+    }
+
+    /// Construct a new frame by applying a given function to each
+    /// local slot.
+    pub fn map<U,F:Fn(&T)->U>(&self, f: F) -> Frame<U> {
+        Frame{return_pc: self.return_pc, locals: self.locals.iter().map(f).collect()}
+    }
+}
+
+/// A machine state which has a call stack, in addition to its
+/// (operand) stack.
+pub trait CallState : MachineState {
+    /// Get the number of frames on the call stack.
+    fn depth(&self) -> usize;
+
+    /// Get the current (i.e. topmost) frame, if any.
+    fn frame(&self) -> Option<&Frame<Self::Word>>;
+
+    /// Get the current (i.e. topmost) frame mutably, if any.
+    fn frame_mut(&mut self) -> Option<&mut Frame<Self::Word>>;
+
+    /// Push a frame onto the call stack, or fail with
+    /// `call_depth_exceeded()` if the call stack is full.
+    fn call(&mut self, frame: Frame<Self::Word>) -> Result<(),Self::Error>;
+
+    /// Pop the current frame from the call stack, or fail with
+    /// `invalid_return()` if there is none.
+    fn ret(&mut self) -> Result<Frame<Self::Word>,Self::Error>;
+}
@@ -0,0 +1,194 @@
+use crate::{Bytecode,EvmExpansionCost,ExpansionCost,Machine,MachineError,MachineState,MachineWord,Memory};
+use crate::{MemoryState,StepMachine,Term,Verifiable,expanded_size};
+
+// ===================================================================
+// Gas
+// ===================================================================
+
+/// A cost model which determines the gas consumed by executing a
+/// given instruction in a given state.  The cost may depend upon the
+/// state (e.g. the extent of a memory access) but is always known,
+/// even when the gas remaining is not.
+pub trait GasSchedule<M:Machine> {
+    fn cost(&self, insn: &M::Instruction, state: &M::State) -> u64;
+}
+
+/// Any suitable function can be used as a cost model.
+impl<M:Machine,F> GasSchedule<M> for F
+where F: Fn(&M::Instruction,&M::State)->u64 {
+    fn cost(&self, insn: &M::Instruction, state: &M::State) -> u64 {
+        self(insn,state)
+    }
+}
+
+/// A machine state which has a gas counter, in addition to its stack.
+/// Since the gas remaining is a word, it may be abstract (or
+/// symbolic) and is bounded by the width of a word.
+pub trait GasState : MachineState {
+    /// Get the gas remaining in this state, or `None` if execution is
+    /// not metered.
+    fn gas(&self) -> Option<&Self::Word>;
+
+    /// Set the gas remaining in this state.
+    fn set_gas(&mut self, gas: Self::Word);
+}
+
+/// A machine which executes a given machine, but charges the cost of
+/// each instruction (according to a given schedule) against the gas
+/// remaining in the state beforehand.  Execution fails with
+/// `out_of_gas()` when the cost definitely exceeds the gas remaining.
+/// Otherwise, when verifying, the corresponding obligation is that it
+/// does not.  States whose execution is not metered are never
+/// charged.
+pub struct GasMachine<'a,M,G> {
+    machine: &'a M,
+    schedule: G
+}
+
+impl<'a,M,G> GasMachine<'a,M,G> {
+    pub fn new(machine: &'a M, schedule: G) -> Self {
+        Self{machine,schedule}
+    }
+}
+
+impl<'a,M:Machine,G:GasSchedule<M>> GasMachine<'a,M,G>
+where M::State: GasState, <M::State as MachineState>::Word: Clone {
+    /// Determine the cost of executing a given state, along with the
+    /// gas remaining beforehand (provided execution is metered).  If
+    /// the cost does not fit into a word then it definitely exceeds
+    /// the gas remaining and, hence, is `None`.
+    fn charge(&self, state: &M::State) -> Option<(Option<Word<M>>,Word<M>)> {
+        let gas = state.gas()?.clone();
+        let insn = self.machine.get(state.pc()).ok()?;
+        let cost = self.schedule.cost(insn, state);
+        let word = gas.constant_like(cost);
+        let fits = word.to_bitvec().and_then(|c| c.to_u64()) == Some(cost);
+        Some((fits.then_some(word), gas))
+    }
+}
+
+type Word<M> = <<M as Machine>::State as MachineState>::Word;
+
+impl<'a,M:Machine,G:GasSchedule<M>> Machine for GasMachine<'a,M,G>
+where M::State: GasState, M::Error: MachineError, Word<M>: Clone {
+    type State = M::State;
+    type Error = M::Error;
+    type Outcome = M::Outcome;
+    type Instruction = M::Instruction;
+
+    fn get(&self, pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.machine.get(pc)
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        if let Some((cost,gas)) = self.charge(&state) {
+            let Some(cost) = cost else { return Err(M::Error::out_of_gas()); };
+            if gas.clone().less_than(cost.clone()).to_bool() == Some(true) {
+                return Err(M::Error::out_of_gas());
+            }
+            state.set_gas(gas.sub(cost));
+        }
+        self.machine.execute(state)
+    }
+}
+
+impl<'a,M:StepMachine,G:GasSchedule<M>> StepMachine for GasMachine<'a,M,G>
+where M::State: GasState, M::Error: MachineError, Word<M>: Clone {
+    type Value = M::Value;
+}
+
+/// The obligation of each instruction is that of the underlying
+/// machine, along with there being sufficient gas to execute it.
+impl<'a,M:Verifiable,G:GasSchedule<M>> Verifiable for GasMachine<'a,M,G>
+where M::State: GasState+MachineState<Word=Term>, M::Error: MachineError {
+    fn obligation(&self, state: &Self::State) -> Option<Term> {
+        let ob = self.machine.obligation(state);
+        let Some((cost,gas)) = self.charge(state) else { return ob; };
+        let enough = match cost {
+            Some(cost) => gas.less_than(cost).nonzero().not(),
+            None => Term::bool(false)
+        };
+        match ob {
+            Some(ob) => Some(ob.nonzero().and(enough)),
+            None => Some(enough)
+        }
+    }
+
+    fn accesses(&self, state: &Self::State) -> bool {
+        self.machine.accesses(state)
+    }
+
+    fn guard(&self, state: &Self::State, successor: &Self::State) -> Term {
+        self.machine.guard(state, successor)
+    }
+}
+
+// ===================================================================
+// Reference Schedule
+// ===================================================================
+
+/// A cost model for the reference machine, loosely based on that of
+/// the EVM.  Accessing memory additionally costs the expansion of
+/// memory (according to a given `ExpansionCost`).  When the address
+/// being accessed is unknown, the worst case is charged (i.e. that
+/// memory expands to its limit).
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct StackGasSchedule<X=EvmExpansionCost> {
+    expansion: X
+}
+
+impl StackGasSchedule {
+    pub fn new() -> Self {
+        Self{expansion: EvmExpansionCost}
+    }
+}
+
+impl Default for StackGasSchedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<X> StackGasSchedule<X> {
+    /// Construct a schedule with a given cost of expanding memory.
+    pub fn with_expansion(expansion: X) -> Self {
+        Self{expansion}
+    }
+
+    /// Determine the base cost of a given instruction.
+    pub fn base_cost<T>(insn: &Bytecode<T>) -> u64 {
+        match insn {
+            Bytecode::Push(_)|Bytecode::Dup(_)|Bytecode::Swap(_) => 3,
+            Bytecode::Pop => 2,
+            Bytecode::Eq|Bytecode::Neq|Bytecode::Lt|Bytecode::LtEq|Bytecode::Gt|Bytecode::GtEq => 3,
+            Bytecode::Add|Bytecode::Sub => 3,
+            Bytecode::Mul|Bytecode::Div|Bytecode::Rem => 5,
+            Bytecode::MLoad(_)|Bytecode::MStore(_) => 3,
+            Bytecode::SLoad|Bytecode::SStore => 100,
+            Bytecode::Assert => 1,
+            Bytecode::Jump(_) => 8,
+            Bytecode::JumpIf(_) => 10,
+            Bytecode::Call(_,_) => 10,
+            Bytecode::Ret => 8,
+            Bytecode::Local(_)|Bytecode::SetLocal(_) => 3,
+            Bytecode::Invalid(_)|Bytecode::Halt|Bytecode::Revert|Bytecode::Return => 0
+        }
+    }
+}
+
+impl<X:ExpansionCost,M:Machine<Instruction=Bytecode<Word<M>>>> GasSchedule<M> for StackGasSchedule<X>
+where M::State: MemoryState {
+    fn cost(&self, insn: &Bytecode<Word<M>>, state: &M::State) -> u64 {
+        let mut cost = Self::base_cost(insn);
+        if let Bytecode::MLoad(n)|Bytecode::MStore(n) = insn {
+            let old = state.memory().size();
+            let new = match state.peek(0).ok().and_then(|a| a.to_usize()) {
+                Some(address) => expanded_size(old, address, *n),
+                // Unknown addresses may expand memory to its limit
+                None => old.max(state.memory().limit())
+            };
+            cost = cost.saturating_add(self.expansion.expansion_cost(old, new));
+        }
+        cost
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use crate::{BitVec,Lattice,MachineWord};
+
+/// An abstract word representing every (unsigned) value within a
+/// given range `[lo,hi]` of bitvectors.  This provides an abstract
+/// domain whose transfer functions soundly over-approximate those of
+/// `BitVec`, including wrap-around on overflow and the convention
+/// that division (or remainder) by zero produces zero.  The empty
+/// interval represents the absence of any value.
+#[derive(Clone,PartialEq,Eq,Hash)]
+pub struct Interval {
+    lo: BitVec,
+    hi: BitVec
+}
+
+impl Interval {
+    /// Construct the interval `[lo,hi]`.  If `lo > hi` then the empty
+    /// interval is returned.
+    pub fn new(lo: BitVec, hi: BitVec) -> Self {
+        assert_eq!(lo.width(), hi.width(), "interval width mismatch");
+        if lo > hi {
+            Self::empty(lo.width())
+        } else {
+            Self{lo,hi}
+        }
+    }
+
+    /// Construct the interval containing exactly one value.
+    pub fn constant(value: BitVec) -> Self {
+        Self{lo: value.clone(), hi: value}
+    }
+
+    /// Construct the interval containing every value of a given
+    /// width.
+    pub fn full(width: usize) -> Self {
+        Self{lo: BitVec::zero(width), hi: BitVec::ones(width)}
+    }
+
+    /// Construct the interval containing no values of a given width.
+    pub fn empty(width: usize) -> Self {
+        // Canonical representation of the empty interval.
+        Self{lo: BitVec::ones(width), hi: BitVec::zero(width)}
+    }
+
+    /// Get the width (in bits) of values in this interval.
+    pub fn width(&self) -> usize { self.lo.width() }
+
+    /// Get the lower bound of this interval.
+    pub fn lo(&self) -> &BitVec { &self.lo }
+
+    /// Get the upper bound of this interval.
+    pub fn hi(&self) -> &BitVec { &self.hi }
+
+    /// Check whether this interval contains no values.
+    pub fn is_empty(&self) -> bool { self.lo > self.hi }
+
+    /// Check whether this interval contains every value.
+    pub fn is_full(&self) -> bool {
+        self.lo.is_zero() && self.hi == BitVec::ones(self.width())
+    }
+
+    /// Check whether a given value is contained in this interval.
+    pub fn contains(&self, value: &BitVec) -> bool {
+        &self.lo <= value && value <= &self.hi
+    }
+
+    /// Get the value of this interval, if it contains exactly one.
+    pub fn as_constant(&self) -> Option<&BitVec> {
+        if self.lo == self.hi { Some(&self.lo) } else { None }
+    }
+
+    /// Construct the interval `[0,1]` representing an unknown
+    /// boolean.
+    fn boolean(width: usize) -> Self {
+        Self{lo: BitVec::zero(width), hi: BitVec::from_u64(width,1)}
+    }
+
+    /// Construct an interval representing a known boolean.
+    fn bool(width: usize, b: bool) -> Self {
+        Self::constant(BitVec::from_u64(width, b as u64))
+    }
+
+    /// Apply a binary operation to two intervals, handling the cases
+    /// where either is empty, or both are constant.  Otherwise, the
+    /// given transfer function is applied.
+    fn lift<F,G>(self, rhs: Self, concrete: F, abs: G) -> Self
+    where F: Fn(BitVec,BitVec)->BitVec, G: Fn(Self,Self)->Self {
+        assert_eq!(self.width(), rhs.width(), "interval width mismatch");
+        if self.is_empty() || rhs.is_empty() {
+            Self::empty(self.width())
+        } else if let (Some(l),Some(r)) = (self.as_constant(),rhs.as_constant()) {
+            Self::constant(concrete(l.clone(),r.clone()))
+        } else {
+            abs(self,rhs)
+        }
+    }
+}
+
+/// Determine the smallest value of the form `2^n - 1` which is
+/// greater or equal to a given value.
+fn mask(v: &BitVec) -> BitVec {
+    let w = v.width();
+    let n = w - v.leading_zeros();
+    let mut r = BitVec::zero(w);
+    (0..n).for_each(|i| r.set_bit(i,true));
+    r
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            write!(f, "[]")
+        } else {
+            write!(f, "[{},{}]", self.lo, self.hi)
+        }
+    }
+}
+
+impl Lattice for Interval {
+    fn bottom(&self) -> Self {
+        Self::empty(self.width())
+    }
+
+    fn top(&self) -> Self {
+        Self::full(self.width())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            other.clone()
+        } else if other.is_empty() {
+            self.clone()
+        } else {
+            let lo = (&self.lo).min(&other.lo).clone();
+            let hi = (&self.hi).max(&other.hi).clone();
+            Self{lo,hi}
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let lo = (&self.lo).max(&other.lo).clone();
+        let hi = (&self.hi).min(&other.hi).clone();
+        Self::new(lo,hi)
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_empty() || (other.lo <= self.lo && self.hi <= other.hi)
+    }
+
+    /// Any bound which is not stable is pushed to its extreme.
+    fn widen(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            return other.clone();
+        } else if other.is_empty() {
+            return self.clone();
+        }
+        let w = self.width();
+        let lo = if other.lo < self.lo { BitVec::zero(w) } else { self.lo.clone() };
+        let hi = if other.hi > self.hi { BitVec::ones(w) } else { self.hi.clone() };
+        Self{lo,hi}
+    }
+
+    /// Any bound which is at its extreme is refined.
+    fn narrow(&self, other: &Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return self.bottom();
+        }
+        let w = self.width();
+        let lo = if self.lo.is_zero() { other.lo.clone() } else { self.lo.clone() };
+        let hi = if self.hi == BitVec::ones(w) { other.hi.clone() } else { self.hi.clone() };
+        Self::new(lo,hi)
+    }
+}
+
+impl MachineWord for Interval {
+    // Comparators
+    fn less_than(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::less_than, |l,r| {
+            let w = l.width();
+            if l.hi < r.lo {
+                Self::bool(w,true)
+            } else if l.lo >= r.hi {
+                Self::bool(w,false)
+            } else {
+                Self::boolean(w)
+            }
+        })
+    }
+    fn equal(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::equal, |l,r| {
+            let w = l.width();
+            if l.meet(&r).is_empty() {
+                Self::bool(w,false)
+            } else {
+                Self::boolean(w)
+            }
+        })
+    }
+    // Arithmetic
+    fn add(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::add, |l,r| {
+            let (lo,o1) = l.lo.overflowing_add(&r.lo);
+            let (hi,o2) = l.hi.overflowing_add(&r.hi);
+            // Both bounds must wrap (or not) together
+            if o1 == o2 { Self{lo,hi} } else { Self::full(l.width()) }
+        })
+    }
+    fn sub(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::sub, |l,r| {
+            let (lo,o1) = l.lo.overflowing_sub(&r.hi);
+            let (hi,o2) = l.hi.overflowing_sub(&r.lo);
+            // Both bounds must wrap (or not) together
+            if o1 == o2 { Self{lo,hi} } else { Self::full(l.width()) }
+        })
+    }
+    fn mul(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::mul, |l,r| {
+            let (hi,o) = l.hi.overflowing_mul(&r.hi);
+            if o {
+                Self::full(l.width())
+            } else {
+                Self{lo: l.lo.mul(r.lo), hi}
+            }
+        })
+    }
+    fn div(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::div, |l,r| {
+            let w = l.width();
+            if r.hi.is_zero() {
+                Self::constant(BitVec::zero(w))
+            } else if r.lo.is_zero() {
+                // Division by zero gives zero
+                Self{lo: BitVec::zero(w), hi: l.hi}
+            } else {
+                Self{lo: l.lo.div(r.hi), hi: l.hi.div(r.lo)}
+            }
+        })
+    }
+    fn rem(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::rem, |l,r| {
+            let w = l.width();
+            if r.hi.is_zero() {
+                Self::constant(BitVec::zero(w))
+            } else if !r.lo.is_zero() && l.hi < r.lo {
+                // Remainder has no effect
+                l
+            } else {
+                let bound = r.hi.overflowing_sub(&BitVec::from_u64(w,1)).0;
+                Self{lo: BitVec::zero(w), hi: l.hi.min(bound)}
+            }
+        })
+    }
+    fn neg(self)->Self {
+        let w = self.width();
+        if self.is_empty() || self.is_full() {
+            self
+        } else if self.lo.is_zero() && !self.hi.is_zero() {
+            // Contains zero and at least one other value
+            Self::full(w)
+        } else {
+            Self{lo: self.hi.neg(), hi: self.lo.neg()}
+        }
+    }
+    // Bitwise
+    fn and(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::and, |l,r| {
+            Self{lo: BitVec::zero(l.width()), hi: l.hi.min(r.hi)}
+        })
+    }
+    fn or(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::or, |l,r| {
+            let hi = mask((&l.hi).max(&r.hi));
+            Self{lo: l.lo.max(r.lo), hi}
+        })
+    }
+    fn xor(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::xor, |l,r| {
+            let hi = mask((&l.hi).max(&r.hi));
+            Self{lo: BitVec::zero(l.width()), hi}
+        })
+    }
+    fn not(self)->Self {
+        if self.is_empty() {
+            self
+        } else {
+            Self{lo: self.hi.not(), hi: self.lo.not()}
+        }
+    }
+    // Truthiness
+    fn to_bool(&self)->Option<bool> {
+        let zero = BitVec::zero(self.width());
+        if self.is_empty() {
+            None
+        } else if !self.contains(&zero) {
+            Some(true)
+        } else if self.hi.is_zero() {
+            Some(false)
+        } else {
+            None
+        }
+    }
+    // Conversions
+    fn constant_like(&self, value: u64)->Self {
+        Self::constant(BitVec::from_u64(self.width(), value))
+    }
+    fn to_usize(&self)->Option<usize> {
+        self.as_constant().and_then(|v| v.to_usize())
+    }
+    fn to_bitvec(&self)->Option<BitVec> {
+        self.as_constant().cloned()
+    }
+}
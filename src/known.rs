@@ -0,0 +1,326 @@
+use std::fmt;
+use crate::{BitVec,Lattice,MachineWord};
+
+/// An abstract word which tracks, for each bit, whether it is
+/// definitely `0`, definitely `1` or unknown (i.e. a _tristate_
+/// number).  This is particularly effective at establishing alignment
+/// and masking properties, and soundly over-approximates the
+/// operations of `BitVec` (including the convention that division or
+/// remainder by zero produces zero).  Should any bit be known as both
+/// `0` and `1`, then the word represents no values at all.
+#[derive(Clone,PartialEq,Eq,Hash)]
+pub struct KnownBits {
+    /// Bits which are definitely `0`.
+    zeros: BitVec,
+    /// Bits which are definitely `1`.
+    ones: BitVec
+}
+
+impl KnownBits {
+    /// Construct a word from masks indicating those bits known to be
+    /// `0` and those known to be `1`.
+    pub fn new(zeros: BitVec, ones: BitVec) -> Self {
+        assert_eq!(zeros.width(), ones.width(), "known bits width mismatch");
+        Self{zeros,ones}
+    }
+
+    /// Construct a word where every bit is known.
+    pub fn constant(value: BitVec) -> Self {
+        Self{zeros: value.clone().not(), ones: value}
+    }
+
+    /// Construct a word of a given width where no bits are known.
+    pub fn unknown(width: usize) -> Self {
+        Self{zeros: BitVec::zero(width), ones: BitVec::zero(width)}
+    }
+
+    /// Construct a word of a given width which represents no values.
+    pub fn empty(width: usize) -> Self {
+        Self{zeros: BitVec::ones(width), ones: BitVec::ones(width)}
+    }
+
+    /// Get the width (in bits) of this word.
+    pub fn width(&self) -> usize { self.zeros.width() }
+
+    /// Get the mask of bits known to be `0`.
+    pub fn zeros(&self) -> &BitVec { &self.zeros }
+
+    /// Get the mask of bits known to be `1`.
+    pub fn ones(&self) -> &BitVec { &self.ones }
+
+    /// Get the mask of bits which are known (either way).
+    pub fn known(&self) -> BitVec {
+        self.zeros.clone().or(self.ones.clone())
+    }
+
+    /// Check whether this word represents no values (i.e. some bit
+    /// is known to be both `0` and `1`).
+    pub fn is_empty(&self) -> bool {
+        !self.zeros.clone().and(self.ones.clone()).is_zero()
+    }
+
+    /// Get the value of this word, if every bit is known.
+    pub fn as_constant(&self) -> Option<&BitVec> {
+        if !self.is_empty() && self.known() == BitVec::ones(self.width()) {
+            Some(&self.ones)
+        } else {
+            None
+        }
+    }
+
+    /// Check whether a given value is represented by this word.
+    pub fn contains(&self, value: &BitVec) -> bool {
+        value.clone().and(self.zeros.clone()).is_zero()
+            && value.clone().and(self.ones.clone()) == self.ones
+    }
+
+    /// Get the smallest (unsigned) value represented by this word.
+    pub fn min_value(&self) -> BitVec { self.ones.clone() }
+
+    /// Get the largest (unsigned) value represented by this word.
+    pub fn max_value(&self) -> BitVec { self.zeros.clone().not() }
+
+    /// Number of least significant bits known to be `0`.
+    fn trailing_known_zeros(&self) -> usize {
+        self.zeros.clone().not().trailing_zeros()
+    }
+
+    /// Number of least significant bits which are known.
+    fn trailing_known(&self) -> usize {
+        self.known().not().trailing_zeros()
+    }
+
+    /// Number of most significant bits known to be `0`.
+    fn leading_known_zeros(&self) -> usize {
+        self.zeros.clone().not().leading_zeros()
+    }
+
+    /// Construct a word whose most significant `n` bits are known to
+    /// be `0`, and all others are unknown.
+    fn leading_zeros(width: usize, n: usize) -> Self {
+        let mut zeros = BitVec::zero(width);
+        (width-n..width).for_each(|i| zeros.set_bit(i,true));
+        Self{zeros, ones: BitVec::zero(width)}
+    }
+
+    /// Construct a word where only the least significant bit is
+    /// unknown, or known to be a given boolean.
+    fn bool(width: usize, b: Option<bool>) -> Self {
+        match b {
+            Some(b) => Self::constant(BitVec::from_u64(width, b as u64)),
+            None => Self::leading_zeros(width, width - 1)
+        }
+    }
+
+    /// Apply a binary operation to two words, handling the cases
+    /// where either is empty, or both are constant.  Otherwise, the
+    /// given transfer function is applied.
+    fn lift<F,G>(self, rhs: Self, concrete: F, abs: G) -> Self
+    where F: Fn(BitVec,BitVec)->BitVec, G: Fn(Self,Self)->Self {
+        assert_eq!(self.width(), rhs.width(), "known bits width mismatch");
+        if self.is_empty() || rhs.is_empty() {
+            Self::empty(self.width())
+        } else if let (Some(l),Some(r)) = (self.as_constant(),rhs.as_constant()) {
+            Self::constant(concrete(l.clone(),r.clone()))
+        } else {
+            abs(self,rhs)
+        }
+    }
+}
+
+/// Construct a bitvector whose least significant `n` bits are set.
+fn low_mask(width: usize, n: usize) -> BitVec {
+    let mut r = BitVec::zero(width);
+    (0..n.min(width)).for_each(|i| r.set_bit(i,true));
+    r
+}
+
+impl fmt::Debug for KnownBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Words are displayed as binary strings (most significant bit
+/// first), where `?` indicates an unknown bit and `!` a conflicting
+/// bit.
+impl fmt::Display for KnownBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0b")?;
+        for i in (0..self.width()).rev() {
+            let c = match (self.zeros.bit(i),self.ones.bit(i)) {
+                (false,false) => '?',
+                (true,false) => '0',
+                (false,true) => '1',
+                (true,true) => '!'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Since the lattice of known bits has small height, widening and
+/// narrowing are simply join and meet.
+impl Lattice for KnownBits {
+    fn bottom(&self) -> Self {
+        Self::empty(self.width())
+    }
+
+    fn top(&self) -> Self {
+        Self::unknown(self.width())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            other.clone()
+        } else if other.is_empty() {
+            self.clone()
+        } else {
+            let zeros = self.zeros.clone().and(other.zeros.clone());
+            let ones = self.ones.clone().and(other.ones.clone());
+            Self{zeros,ones}
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let r = Self{
+            zeros: self.zeros.clone().or(other.zeros.clone()),
+            ones: self.ones.clone().or(other.ones.clone())
+        };
+        if r.is_empty() { self.bottom() } else { r }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_empty() || (!other.is_empty()
+            && other.zeros.clone().and(self.zeros.clone()) == other.zeros
+            && other.ones.clone().and(self.ones.clone()) == other.ones)
+    }
+}
+
+impl MachineWord for KnownBits {
+    // Comparators
+    fn less_than(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::less_than, |l,r| {
+            let w = l.width();
+            if l.max_value() < r.min_value() {
+                Self::bool(w, Some(true))
+            } else if l.min_value() >= r.max_value() {
+                Self::bool(w, Some(false))
+            } else {
+                Self::bool(w, None)
+            }
+        })
+    }
+    fn equal(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::equal, |l,r| {
+            let w = l.width();
+            let conflict = l.ones.clone().and(r.zeros.clone())
+                .or(l.zeros.clone().and(r.ones.clone()));
+            if conflict.is_zero() {
+                Self::bool(w, None)
+            } else {
+                Self::bool(w, Some(false))
+            }
+        })
+    }
+    // Arithmetic
+    fn add(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::add, |l,r| {
+            // Following the approach of LLVM, determine the carry
+            // bits from the smallest and largest possible sums.
+            let sum_max = l.max_value().add(r.max_value());
+            let sum_min = l.min_value().add(r.min_value());
+            let carry_zero = sum_max.clone().xor(l.zeros.clone()).xor(r.zeros.clone()).not();
+            let carry_one = sum_min.clone().xor(l.ones.clone()).xor(r.ones.clone());
+            let known = l.known().and(r.known()).and(carry_zero.or(carry_one));
+            Self{zeros: sum_max.not().and(known.clone()), ones: sum_min.and(known)}
+        })
+    }
+    fn sub(self,rhs:Self)->Self {
+        self.add(rhs.neg())
+    }
+    fn mul(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::mul, |l,r| {
+            let w = l.width();
+            // Low bits of the product depend only on the low bits of
+            // the operands.
+            let k = l.trailing_known().min(r.trailing_known());
+            let mask = low_mask(w, k);
+            let prod = l.min_value().mul(r.min_value());
+            // Trailing zeros accumulate
+            let tz = l.trailing_known_zeros() + r.trailing_known_zeros();
+            let zeros = prod.clone().not().and(mask.clone()).or(low_mask(w,tz));
+            Self{zeros, ones: prod.and(mask)}
+        })
+    }
+    fn div(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::div, |l,_| {
+            // Quotient cannot exceed the dividend
+            Self::leading_zeros(l.width(), l.leading_known_zeros())
+        })
+    }
+    fn rem(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::rem, |l,r| {
+            let w = l.width();
+            match r.as_constant() {
+                Some(v) if !v.is_zero() && v.clone().and(v.clone().add(BitVec::ones(w))).is_zero() => {
+                    // Remainder by a power of two is a mask
+                    l.and(Self::constant(v.clone().add(BitVec::ones(w))))
+                }
+                _ => {
+                    // Remainder cannot exceed either operand
+                    let n = l.leading_known_zeros().max(r.leading_known_zeros());
+                    Self::leading_zeros(w, n)
+                }
+            }
+        })
+    }
+    fn neg(self)->Self {
+        let one = Self::constant(BitVec::from_u64(self.width(),1));
+        self.not().add(one)
+    }
+    // Bitwise
+    fn and(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::and, |l,r| {
+            Self{zeros: l.zeros.or(r.zeros), ones: l.ones.and(r.ones)}
+        })
+    }
+    fn or(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::or, |l,r| {
+            Self{zeros: l.zeros.and(r.zeros), ones: l.ones.or(r.ones)}
+        })
+    }
+    fn xor(self,rhs:Self)->Self {
+        self.lift(rhs, BitVec::xor, |l,r| {
+            let known = l.known().and(r.known());
+            let v = l.ones.xor(r.ones);
+            Self{zeros: v.clone().not().and(known.clone()), ones: v.and(known)}
+        })
+    }
+    fn not(self)->Self {
+        Self{zeros: self.ones, ones: self.zeros}
+    }
+    // Truthiness
+    fn to_bool(&self)->Option<bool> {
+        if self.is_empty() {
+            None
+        } else if !self.ones.is_zero() {
+            Some(true)
+        } else if self.zeros == BitVec::ones(self.width()) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+    // Conversions
+    fn constant_like(&self, value: u64)->Self {
+        Self::constant(BitVec::from_u64(self.width(), value))
+    }
+    fn to_usize(&self)->Option<usize> {
+        self.as_constant().and_then(|v| v.to_usize())
+    }
+    fn to_bitvec(&self)->Option<BitVec> {
+        self.as_constant().cloned()
+    }
+}
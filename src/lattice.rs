@@ -0,0 +1,50 @@
+/// A (complete) lattice of abstract values, as used by abstract
+/// interpreters to merge words (or states) at control-flow join
+/// points.  Since the _shape_ of an abstract value (e.g. its width,
+/// or the size of a stack) is only known at runtime, elements such as
+/// `bottom()` and `top()` are constructed from an existing element of
+/// the lattice in question.
+pub trait Lattice : Sized {
+    /// Construct the least element of the lattice containing this
+    /// element (e.g. of the same width).
+    fn bottom(&self) -> Self;
+
+    /// Construct the greatest element of the lattice containing this
+    /// element (e.g. of the same width).
+    fn top(&self) -> Self;
+
+    /// Compute the least upper bound of this and another element.
+    fn join(&self, other: &Self) -> Self;
+
+    /// Compute the greatest lower bound of this and another element.
+    fn meet(&self, other: &Self) -> Self;
+
+    /// Check whether this element is below (or equal to) another
+    /// element.
+    fn leq(&self, other: &Self) -> bool;
+
+    /// Check whether this and another element belong to the same
+    /// lattice (e.g. are of the same shape) and, hence, can be
+    /// combined.  By default, this always holds.
+    fn compatible(&self, _other: &Self) -> bool {
+        true
+    }
+
+    /// Widen this element by another, producing an upper bound of
+    /// both.  Unlike `join()`, repeated widening is guaranteed to
+    /// stabilise and, hence, can be used to accelerate fixpoint
+    /// computations over lattices of infinite (or large) height.  By
+    /// default, this is simply `join()` which is sufficient for
+    /// lattices of small height.
+    fn widen(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    /// Narrow this element by another, recovering precision lost
+    /// through widening.  The result lies between `other` and this
+    /// element.  By default, this is simply `meet()` which is
+    /// sufficient for lattices of small height.
+    fn narrow(&self, other: &Self) -> Self {
+        self.meet(other)
+    }
+}
@@ -1,9 +1,49 @@
+mod array;
+mod bitvec;
+mod blast;
+mod context;
 mod error;
+mod explore;
+mod fixpoint;
+mod frame;
+mod gas;
+mod interval;
+mod known;
+mod lattice;
 mod machine;
+mod memory;
+mod sat;
+mod smt;
+mod stack;
+mod step;
+mod storage;
+mod summary;
+mod term;
+mod vcgen;
 mod words;
 mod vec;
 
+pub use array::*;
+pub use bitvec::*;
+pub use blast::*;
+pub use context::*;
 pub use error::*;
+pub use explore::*;
+pub use fixpoint::*;
+pub use frame::*;
+pub use gas::*;
+pub use interval::*;
+pub use known::*;
+pub use lattice::*;
 pub use machine::*;
+pub use memory::*;
+pub use sat::*;
+pub use smt::*;
+pub use stack::*;
+pub use step::*;
+pub use storage::*;
+pub use summary::*;
+pub use term::*;
+pub use vcgen::*;
 pub use vec::*;
 pub use words::*;
@@ -1,4 +1,8 @@
-/// A minimal notion of machine error.
+use crate::{BitVec};
+
+/// A minimal notion of machine error.  Constructors for faults which
+/// carry context (e.g. the target of an invalid jump) accept it, but
+/// implementations are free to discard it.
 pub trait MachineError {
     /// Indicates an attempt was made to read an instruction that does
     /// not exist (e.g. is beyond the end of the code section).
@@ -6,6 +10,38 @@ pub trait MachineError {
     /// Indicates an attempt was made to pop an item from an empty
     /// stack.
     fn stack_underflow() -> Self;
+    /// Indicates an attempt was made to push an item onto a full
+    /// stack.
+    fn stack_overflow() -> Self;
+    /// Indicates an attempt was made to divide (or take the remainder)
+    /// by zero.
+    fn division_by_zero() -> Self;
+    /// Indicates an attempt was made to execute an instruction whose
+    /// opcode is invalid (or unknown).
+    fn invalid_opcode(opcode: u8) -> Self;
+    /// Indicates an attempt was made to jump to a position which is
+    /// not a valid destination.
+    fn invalid_jump(target: usize) -> Self;
+    /// Indicates that execution exhausted its available gas.
+    fn out_of_gas() -> Self;
+    /// Indicates that an assertion did not hold.
+    fn assertion_failure() -> Self;
+    /// Indicates an attempt was made to access memory (or storage) at
+    /// an invalid (or unknown) address.
+    fn invalid_address() -> Self;
+    /// Indicates an attempt was made to store a value which is not
+    /// known where a known value is required (e.g. in a concrete
+    /// memory).
+    fn unknown_value() -> Self;
+    /// Indicates an attempt was made to call a subroutine when the
+    /// maximum call depth had been reached.
+    fn call_depth_exceeded() -> Self;
+    /// Indicates an attempt was made to access a given local slot
+    /// which does not exist (e.g. outside of any subroutine).
+    fn invalid_local(slot: usize) -> Self;
+    /// Indicates an attempt was made to return from a subroutine when
+    /// the call stack was empty.
+    fn invalid_return() -> Self;
 }
 
 /// An abstract "machine" which can be used to (abstractly) execute
@@ -96,6 +132,7 @@ pub trait MachineWord {
     fn equal(self,rhs:Self)->Self;
     // Arithmetic
     fn add(self,rhs:Self)->Self;
+    fn sub(self,rhs:Self)->Self;
     fn mul(self,rhs:Self)->Self;
     fn div(self,rhs:Self)->Self;
     fn rem(self,rhs:Self)->Self;
@@ -105,4 +142,20 @@ pub trait MachineWord {
     fn or(self,rhs:Self)->Self;
     fn xor(self,rhs:Self)->Self;
     fn not(self)->Self;
+    // Truthiness
+    /// Determine whether this word is known to be non-zero (i.e.
+    /// `Some(true)`), known to be zero (i.e. `Some(false)`), or
+    /// neither (i.e. `None`).  Concrete words are always known,
+    /// whilst abstract (or symbolic) words may not be.
+    fn to_bool(&self)->Option<bool>;
+    // Conversions
+    /// Construct a constant word of the same width as this word,
+    /// whose value is a given value (truncated as necessary).
+    fn constant_like(&self, value: u64)->Self;
+    /// Determine the value of this word, provided it is known and
+    /// fits into a `usize`.
+    fn to_usize(&self)->Option<usize>;
+    /// Determine the value of this word (at its full width), provided
+    /// it is known.
+    fn to_bitvec(&self)->Option<BitVec>;
 }
@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use crate::{Array,Lattice,MachineError,MachineState,MachineWord,Merge,Term};
+
+/// Number of bytes in a memory word, which is the granularity at
+/// which memory expands (as for the EVM).
+pub const MEMORY_WORD : usize = 32;
+
+/// The default maximum size (in bytes) of a memory.
+pub const DEFAULT_MAX_MEMORY : usize = 1 << 20;
+
+// ===================================================================
+// Memory
+// ===================================================================
+
+/// A byte-addressable linear memory.  Memory is initially zero, and
+/// expands (in multiples of `MEMORY_WORD` bytes) to cover the highest
+/// address accessed.  Loads and stores access a given number of
+/// bytes, where the most significant byte of a word is held at the
+/// lowest address (i.e. memory is big-endian).  If more bytes are
+/// accessed than fit into a word, the word holds the least
+/// significant bytes.
+pub trait Memory {
+    /// Defines the words used for addresses and values.
+    type Word : MachineWord;
+    /// Defines the set of possible errors which can be raised when
+    /// accessing memory.
+    type Error;
+
+    /// Get the size (in bytes) of this memory.
+    fn size(&self) -> usize;
+
+    /// Get the maximum size (in bytes) of this memory, such that any
+    /// access extending beyond it fails.
+    fn limit(&self) -> usize;
+
+    /// Load `n` bytes starting at a given address.
+    fn load(&mut self, address: &Self::Word, n: usize) -> Result<Self::Word,Self::Error>;
+
+    /// Store the least significant `n` bytes of a given word, starting
+    /// at a given address.
+    fn store(&mut self, address: &Self::Word, n: usize, value: Self::Word) -> Result<(),Self::Error>;
+}
+
+/// A machine state which has a linear memory, in addition to its
+/// stack.
+pub trait MemoryState : MachineState {
+    type Memory : Memory<Word=Self::Word,Error=Self::Error>;
+
+    /// Get the memory of this state.
+    fn memory(&self) -> &Self::Memory;
+
+    /// Get the memory of this state (mutably).
+    fn memory_mut(&mut self) -> &mut Self::Memory;
+}
+
+/// Determine the size of a memory of a given size, after accessing
+/// `n` bytes at a given address.
+pub fn expanded_size(size: usize, address: usize, n: usize) -> usize {
+    if n == 0 {
+        size
+    } else {
+        let end = address.saturating_add(n);
+        size.max(end.div_ceil(MEMORY_WORD).saturating_mul(MEMORY_WORD))
+    }
+}
+
+/// A hook for determining the cost of expanding a memory from one
+/// size to another (in bytes).
+pub trait ExpansionCost {
+    fn expansion_cost(&self, old: usize, new: usize) -> u64;
+}
+
+/// The memory expansion cost of the EVM, which is linear in the
+/// number of words for small memories, but quadratic for large
+/// memories.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct EvmExpansionCost;
+
+impl EvmExpansionCost {
+    fn cost(size: usize) -> u64 {
+        let words = size.div_ceil(MEMORY_WORD) as u64;
+        3 * words + (words * words) / 512
+    }
+}
+
+impl ExpansionCost for EvmExpansionCost {
+    fn expansion_cost(&self, old: usize, new: usize) -> u64 {
+        Self::cost(new).saturating_sub(Self::cost(old))
+    }
+}
+
+// ===================================================================
+// Concrete Memory
+// ===================================================================
+
+/// A memory which holds concrete bytes and, hence, requires both
+/// addresses and stored values to be known (failing with
+/// `invalid_address()` or `unknown_value()` otherwise).
+#[derive(Clone,Debug,PartialEq)]
+pub struct ConcreteMemory<T,E> {
+    dummy: PhantomData<(T,E)>,
+    bytes: Vec<u8>,
+    max_size: usize
+}
+
+impl<T,E> ConcreteMemory<T,E> {
+    pub fn new() -> Self {
+        Self{bytes: Vec::new(), max_size: DEFAULT_MAX_MEMORY, dummy: PhantomData}
+    }
+
+    /// Set the maximum size (in bytes) of this memory, beyond which
+    /// accesses fail.
+    pub fn max_size(mut self, n: usize) -> Self {
+        self.max_size = n;
+        self
+    }
+
+    /// Get the bytes of this memory.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<T,E> Default for ConcreteMemory<T,E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T:MachineWord+Clone,E:MachineError> Memory for ConcreteMemory<T,E> {
+    type Word = T;
+    type Error = E;
+
+    fn size(&self) -> usize { self.bytes.len() }
+
+    fn limit(&self) -> usize { self.max_size }
+
+    fn load(&mut self, address: &T, n: usize) -> Result<T,E> {
+        let a = self.expand(address, n)?;
+        let bytes = self.bytes[a..a+n].iter().map(|b| address.constant_like(*b as u64));
+        Ok(from_bytes(address.constant_like(0), bytes))
+    }
+
+    fn store(&mut self, address: &T, n: usize, value: T) -> Result<(),E> {
+        let bytes : Option<Vec<usize>> = to_bytes(value, n).iter().map(|b| b.to_usize()).collect();
+        let bytes = bytes.ok_or_else(E::unknown_value)?;
+        let a = self.expand(address, n)?;
+        for (i,b) in bytes.into_iter().enumerate() {
+            self.bytes[a+i] = b as u8;
+        }
+        Ok(())
+    }
+}
+
+impl<T:MachineWord,E:MachineError> ConcreteMemory<T,E> {
+    /// Expand this memory to cover `n` bytes at a given address,
+    /// returning the address.
+    fn expand(&mut self, address: &T, n: usize) -> Result<usize,E> {
+        let a = address.to_usize().ok_or_else(E::invalid_address)?;
+        let size = expanded_size(self.bytes.len(), a, n);
+        if size > self.max_size { return Err(E::invalid_address()); }
+        self.bytes.resize(size, 0);
+        Ok(a)
+    }
+}
+
+// ===================================================================
+// Word Memory
+// ===================================================================
+
+/// A memory whose bytes are held as words (i.e. words whose value
+/// lies between `0` and `255`), and whose addresses must be known.
+/// As such, the values held in memory may be abstract or symbolic,
+/// making this suitable for abstract (and symbolic) execution.
+/// Bytes which have never been written are zero.
+#[derive(Clone,Debug,PartialEq)]
+pub struct WordMemory<T,E> {
+    dummy: PhantomData<E>,
+    bytes: BTreeMap<usize,T>,
+    size: usize,
+    max_size: usize
+}
+
+impl<T,E> WordMemory<T,E> {
+    pub fn new() -> Self {
+        Self{bytes: BTreeMap::new(), size: 0, max_size: DEFAULT_MAX_MEMORY, dummy: PhantomData}
+    }
+
+    /// Set the maximum size (in bytes) of this memory, beyond which
+    /// accesses fail.
+    pub fn max_size(mut self, n: usize) -> Self {
+        self.max_size = n;
+        self
+    }
+
+    /// Get the byte at a given address, if it has been written.
+    pub fn get(&self, address: usize) -> Option<&T> {
+        self.bytes.get(&address)
+    }
+
+    /// Construct a new memory by applying a given function to each
+    /// byte written.
+    pub fn map<U,F:Fn(&T)->U>(&self, f: F) -> WordMemory<U,E> {
+        let bytes = self.bytes.iter().map(|(a,b)| (*a,f(b))).collect();
+        WordMemory{bytes, size: self.size, max_size: self.max_size, dummy: PhantomData}
+    }
+}
+
+impl<T,E> Default for WordMemory<T,E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T:MachineWord+Clone,E:MachineError> Memory for WordMemory<T,E> {
+    type Word = T;
+    type Error = E;
+
+    fn size(&self) -> usize { self.size }
+
+    fn limit(&self) -> usize { self.max_size }
+
+    fn load(&mut self, address: &T, n: usize) -> Result<T,E> {
+        let a = self.expand(address, n)?;
+        let zero = address.constant_like(0);
+        let bytes = (a..a+n).map(|i| self.bytes.get(&i).cloned().unwrap_or_else(|| zero.clone()));
+        Ok(from_bytes(zero.clone(), bytes))
+    }
+
+    fn store(&mut self, address: &T, n: usize, value: T) -> Result<(),E> {
+        let a = self.expand(address, n)?;
+        for (i,b) in to_bytes(value, n).into_iter().enumerate() {
+            self.bytes.insert(a+i, b);
+        }
+        Ok(())
+    }
+}
+
+impl<T:MachineWord,E:MachineError> WordMemory<T,E> {
+    /// Expand this memory to cover `n` bytes at a given address,
+    /// returning the address.
+    fn expand(&mut self, address: &T, n: usize) -> Result<usize,E> {
+        let a = address.to_usize().ok_or_else(E::invalid_address)?;
+        let size = expanded_size(self.size, a, n);
+        if size > self.max_size { return Err(E::invalid_address()); }
+        self.size = size;
+        Ok(a)
+    }
+}
+
+/// Memories are ordered pointwise over their bytes, where bytes not
+/// written in one memory (but written in the other) are zero.  Since
+/// the bytes of the memory are given by its contents, `top()` cannot
+/// represent an arbitrary memory and, instead, makes every byte
+/// written unknown.
+impl<T:Lattice+MachineWord+Clone,E> Lattice for WordMemory<T,E> {
+    fn bottom(&self) -> Self {
+        self.map(|b| b.bottom())
+    }
+
+    fn top(&self) -> Self {
+        self.map(|b| b.top())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.join(r))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.meet(r))
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.size <= other.size && self.pairs(other).iter().all(|(_,l,r)| l.leq(r))
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.widen(r))
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.narrow(r))
+    }
+}
+
+/// Symbolic memories are merged pointwise over their bytes.
+impl<E> Merge for WordMemory<Term,E> {
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self> {
+        Some(lhs.zip(rhs, |l,r| Term::ite(cond.clone(), l.clone(), r.clone())))
+    }
+}
+
+impl<T:MachineWord+Clone,E> WordMemory<T,E> {
+    /// Pair up the bytes written in either memory, by address.
+    fn pairs(&self, other: &Self) -> Vec<(usize,T,T)> {
+        let mut addresses : Vec<usize> = self.bytes.keys().chain(other.bytes.keys()).copied().collect();
+        addresses.sort();
+        addresses.dedup();
+        addresses.into_iter().map(|a| {
+            let (l,r) = (self.bytes.get(&a),other.bytes.get(&a));
+            let zero = l.or(r).unwrap().constant_like(0);
+            (a, l.cloned().unwrap_or_else(|| zero.clone()), r.cloned().unwrap_or(zero))
+        }).collect()
+    }
+
+    fn zip<F:Fn(&T,&T)->T>(&self, other: &Self, f: F) -> Self {
+        let bytes = self.pairs(other).into_iter().map(|(a,l,r)| (a,f(&l,&r))).collect();
+        let size = self.size.max(other.size);
+        Self{bytes, size, max_size: self.max_size, dummy: PhantomData}
+    }
+}
+
+// ===================================================================
+// Symbolic Memory
+// ===================================================================
+
+/// A memory whose addresses and values may both be symbolic, where
+/// bytes are held as terms (i.e. terms whose value lies between `0`
+/// and `255`) in an `Array`.  Thus, loading from a computed address
+/// yields a conditional over the stores which it may alias.  Since
+/// the extent of an access at an unknown address is itself unknown,
+/// only accesses at known addresses expand the memory (and are
+/// checked against its maximum size).  Otherwise, the access is
+/// assumed to lie within the maximum size, which is an obligation
+/// when verifying (see `Verifiable`).
+#[derive(Clone,Debug,PartialEq)]
+pub struct SymbolicMemory<E> {
+    dummy: PhantomData<E>,
+    bytes: Array,
+    size: usize,
+    max_size: usize
+}
+
+impl<E> SymbolicMemory<E> {
+    pub fn new() -> Self {
+        Self{bytes: Array::new(), size: 0, max_size: DEFAULT_MAX_MEMORY, dummy: PhantomData}
+    }
+
+    /// Set the maximum size (in bytes) of this memory, beyond which
+    /// accesses fail.
+    pub fn max_size(mut self, n: usize) -> Self {
+        self.max_size = n;
+        self
+    }
+
+    /// Get the array holding the bytes of this memory.
+    pub fn bytes(&self) -> &Array {
+        &self.bytes
+    }
+}
+
+impl<E> Default for SymbolicMemory<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E:MachineError> Memory for SymbolicMemory<E> {
+    type Word = Term;
+    type Error = E;
+
+    fn size(&self) -> usize { self.size }
+
+    fn limit(&self) -> usize { self.max_size }
+
+    fn load(&mut self, address: &Term, n: usize) -> Result<Term,E> {
+        self.expand(address, n)?;
+        let bytes = (0..n).map(|i| self.bytes.select(&byte_address(address, i)));
+        Ok(from_bytes(address.constant_like(0), bytes))
+    }
+
+    fn store(&mut self, address: &Term, n: usize, value: Term) -> Result<(),E> {
+        self.expand(address, n)?;
+        for (i,b) in to_bytes(value, n).into_iter().enumerate() {
+            self.bytes.store(byte_address(address, i), b);
+        }
+        Ok(())
+    }
+}
+
+impl<E:MachineError> SymbolicMemory<E> {
+    /// Expand this memory to cover `n` bytes at a given address,
+    /// provided the address is known.
+    fn expand(&mut self, address: &Term, n: usize) -> Result<(),E> {
+        if let Some(a) = address.to_usize() {
+            let size = expanded_size(self.size, a, n);
+            if size > self.max_size { return Err(E::invalid_address()); }
+            self.size = size;
+        } else if address.as_constant().is_some() {
+            // Known, but too large to address.
+            return Err(E::invalid_address());
+        }
+        Ok(())
+    }
+}
+
+/// Symbolic memories are merged elementwise over their arrays.
+impl<E> Merge for SymbolicMemory<E> {
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self> {
+        let bytes = Array::merge(cond, &lhs.bytes, &rhs.bytes);
+        let size = lhs.size.max(rhs.size);
+        Some(Self{bytes, size, max_size: lhs.max_size, dummy: PhantomData})
+    }
+}
+
+// ===================================================================
+// Helpers
+// ===================================================================
+
+/// Determine the address of the `ith` byte from a given address.
+fn byte_address(address: &Term, i: usize) -> Term {
+    address.clone().add(address.constant_like(i as u64))
+}
+
+/// Combine a sequence of bytes (most significant first) into a word,
+/// given the zero word.
+fn from_bytes<T:MachineWord+Clone,I:Iterator<Item=T>>(zero: T, bytes: I) -> T {
+    let base = zero.constant_like(256);
+    bytes.fold(zero, |acc,b| acc.mul(base.clone()).add(b))
+}
+
+/// Split the least significant `n` bytes of a word into a sequence
+/// of bytes (most significant first).
+fn to_bytes<T:MachineWord+Clone>(value: T, n: usize) -> Vec<T> {
+    let mask = value.constant_like(0xff);
+    let base = value.constant_like(256);
+    // Shifting is expressed as division, where a divisor which
+    // overflows the word (hence, is zero) gives zero.
+    let mut shift = value.constant_like(1);
+    let mut bytes = Vec::with_capacity(n);
+    for _ in 0..n {
+        bytes.push(value.clone().div(shift.clone()).and(mask.clone()));
+        shift = shift.mul(base.clone());
+    }
+    bytes.reverse();
+    bytes
+}
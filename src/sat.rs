@@ -0,0 +1,337 @@
+use std::ops::Not;
+
+/// A literal is either a (boolean) variable, or its negation.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Lit(u32);
+
+impl Lit {
+    /// Construct the literal for a given variable, or its negation.
+    pub fn new(var: usize, positive: bool) -> Self {
+        Lit(((var as u32) << 1) | (!positive as u32))
+    }
+
+    /// Get the variable underlying this literal.
+    pub fn var(self) -> usize { (self.0 >> 1) as usize }
+
+    /// Check whether this literal is the variable itself, or its
+    /// negation.
+    pub fn is_positive(self) -> bool { self.0 & 1 == 0 }
+
+    fn index(self) -> usize { self.0 as usize }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit { Lit(self.0 ^ 1) }
+}
+
+/// A conflict-driven clause learning (CDCL) solver for propositional
+/// formulae in conjunctive normal form.  This follows the design of
+/// MiniSat, in that it employs two watched literals for propagation,
+/// first-UIP conflict analysis with non-chronological backtracking,
+/// activity-based (VSIDS) decisions with phase saving, and restarts
+/// following the Luby sequence.  Learnt clauses are never deleted,
+/// which is reasonable for the (small) problems arising from
+/// bit-blasting verification conditions.
+pub struct Solver {
+    /// Both original and learnt clauses, where the first two literals
+    /// of each clause are watched.  For a clause which is the reason
+    /// for an assignment, the first literal is that assigned.
+    clauses: Vec<Vec<Lit>>,
+    /// For each literal, the clauses watching it.
+    watches: Vec<Vec<usize>>,
+    /// Current assignment of each variable.
+    assigns: Vec<Option<bool>>,
+    /// Decision level at which each variable was assigned.
+    level: Vec<usize>,
+    /// Clause responsible for each assignment (if not a decision).
+    reason: Vec<Option<usize>>,
+    /// Assigned literals in chronological order.
+    trail: Vec<Lit>,
+    /// Position within trail where each decision level starts.
+    trail_lim: Vec<usize>,
+    /// Position within trail of next literal to propagate.
+    qhead: usize,
+    activity: Vec<f64>,
+    bump: f64,
+    /// Last value assigned to each variable.
+    polarity: Vec<bool>,
+    /// Satisfying assignment found by last call to `solve()`.
+    model: Vec<bool>,
+    /// False if the clauses are known to be unsatisfiable.
+    ok: bool,
+    conflicts: usize
+}
+
+/// Number of conflicts corresponding to one unit of the restart
+/// sequence.
+const RESTART_UNIT : usize = 100;
+/// Factor by which variable activities decay after each conflict.
+const ACTIVITY_DECAY : f64 = 0.95;
+
+impl Solver {
+    /// Construct a solver with no variables or clauses.
+    pub fn new() -> Self {
+        Self{clauses: Vec::new(), watches: Vec::new(), assigns: Vec::new(), level: Vec::new(),
+             reason: Vec::new(), trail: Vec::new(), trail_lim: Vec::new(), qhead: 0,
+             activity: Vec::new(), bump: 1.0, polarity: Vec::new(), model: Vec::new(),
+             ok: true, conflicts: 0}
+    }
+
+    /// Allocate a fresh variable, returning its positive literal.
+    pub fn new_var(&mut self) -> Lit {
+        let v = self.assigns.len();
+        self.assigns.push(None);
+        self.level.push(0);
+        self.reason.push(None);
+        self.activity.push(0.0);
+        self.polarity.push(false);
+        self.watches.push(Vec::new());
+        self.watches.push(Vec::new());
+        Lit::new(v, true)
+    }
+
+    /// Get the number of variables allocated.
+    pub fn num_vars(&self) -> usize { self.assigns.len() }
+
+    /// Get the number of conflicts encountered so far.
+    pub fn num_conflicts(&self) -> usize { self.conflicts }
+
+    /// Add a clause (i.e. a disjunction of literals) to this solver,
+    /// returning false if the clauses are now known to be
+    /// unsatisfiable.
+    pub fn add_clause(&mut self, lits: &[Lit]) -> bool {
+        assert!(self.trail_lim.is_empty());
+        if !self.ok { return false; }
+        let mut clause = lits.to_vec();
+        clause.sort();
+        clause.dedup();
+        // Discard clauses which are tautologies, or already satisfied,
+        // and literals which are already falsified.
+        if clause.windows(2).any(|w| w[0] == !w[1]) || clause.iter().any(|&l| self.value(l) == Some(true)) {
+            return true;
+        }
+        clause.retain(|&l| self.value(l).is_none());
+        match clause.len() {
+            0 => { self.ok = false; }
+            1 => {
+                self.enqueue(clause[0], None);
+                self.ok = self.propagate().is_none();
+            }
+            _ => { self.attach(clause); }
+        }
+        self.ok
+    }
+
+    /// Determine whether or not the clauses are satisfiable.  If so,
+    /// the satisfying assignment can be obtained using `model()`.
+    pub fn solve(&mut self) -> bool {
+        if !self.ok { return false; }
+        let mut restarts = 0;
+        let mut budget = RESTART_UNIT * luby(restarts);
+        loop {
+            if let Some(confl) = self.propagate() {
+                self.conflicts += 1;
+                if self.trail_lim.is_empty() {
+                    self.ok = false;
+                    return false;
+                }
+                let (learnt,level) = self.analyse(confl);
+                self.backtrack(level);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let lit = learnt[0];
+                    let ci = self.attach(learnt);
+                    self.enqueue(lit, Some(ci));
+                }
+                self.bump /= ACTIVITY_DECAY;
+                budget = budget.saturating_sub(1);
+            } else if budget == 0 {
+                restarts += 1;
+                budget = RESTART_UNIT * luby(restarts);
+                self.backtrack(0);
+            } else if let Some(v) = self.pick() {
+                self.trail_lim.push(self.trail.len());
+                self.enqueue(Lit::new(v, self.polarity[v]), None);
+            } else {
+                self.model = self.assigns.iter().map(|a| a.unwrap()).collect();
+                self.backtrack(0);
+                return true;
+            }
+        }
+    }
+
+    /// Get the value of a given variable in the satisfying assignment
+    /// found by the last (successful) call to `solve()`.
+    pub fn model(&self, var: usize) -> bool {
+        self.model[var]
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.assigns[lit.var()].map(|b| b == lit.is_positive())
+    }
+
+    fn attach(&mut self, clause: Vec<Lit>) -> usize {
+        let ci = self.clauses.len();
+        self.watches[clause[0].index()].push(ci);
+        self.watches[clause[1].index()].push(ci);
+        self.clauses.push(clause);
+        ci
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let v = lit.var();
+        self.assigns[v] = Some(lit.is_positive());
+        self.level[v] = self.trail_lim.len();
+        self.reason[v] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Propagate all enqueued assignments, returning a conflicting
+    /// clause (if any).
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            let falsified = !self.trail[self.qhead];
+            self.qhead += 1;
+            let watching = std::mem::take(&mut self.watches[falsified.index()]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            for ci in watching {
+                if conflict.is_some() {
+                    kept.push(ci);
+                    continue;
+                }
+                // Ensure falsified literal is second
+                let clause = &mut self.clauses[ci];
+                if clause[0] == falsified { clause.swap(0,1); }
+                let first = clause[0];
+                let assigns = &self.assigns;
+                let value = |l:Lit| assigns[l.var()].map(|b| b == l.is_positive());
+                if value(first) == Some(true) {
+                    kept.push(ci);
+                    continue;
+                }
+                // Look for a new literal to watch
+                if let Some(k) = (2..clause.len()).find(|&k| value(clause[k]) != Some(false)) {
+                    clause.swap(1,k);
+                    let lit = clause[1];
+                    self.watches[lit.index()].push(ci);
+                    continue;
+                }
+                // Clause is unit or conflicting
+                kept.push(ci);
+                if value(first) == Some(false) {
+                    conflict = Some(ci);
+                } else {
+                    self.enqueue(first, Some(ci));
+                }
+            }
+            self.watches[falsified.index()] = kept;
+            if conflict.is_some() {
+                self.qhead = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Analyse a conflict to produce a learnt clause (whose first
+    /// literal is the first unique implication point), along with the
+    /// level to backtrack to.
+    fn analyse(&mut self, mut confl: usize) -> (Vec<Lit>,usize) {
+        let current = self.trail_lim.len();
+        let mut seen = vec![false; self.num_vars()];
+        let mut learnt = vec![Lit(0)];
+        let mut counter = 0;
+        let mut index = self.trail.len();
+        let mut uip : Option<Lit> = None;
+        loop {
+            // Skip the implied literal of a reason clause
+            let start = if uip.is_some() { 1 } else { 0 };
+            for k in start..self.clauses[confl].len() {
+                let q = self.clauses[confl][k];
+                let v = q.var();
+                if !seen[v] && self.level[v] > 0 {
+                    seen[v] = true;
+                    self.bump_activity(v);
+                    if self.level[v] == current { counter += 1; } else { learnt.push(q); }
+                }
+            }
+            // Find next literal to expand
+            loop {
+                index -= 1;
+                if seen[self.trail[index].var()] { break; }
+            }
+            let p = self.trail[index];
+            seen[p.var()] = false;
+            counter -= 1;
+            uip = Some(p);
+            if counter == 0 { break; }
+            confl = self.reason[p.var()].unwrap();
+        }
+        learnt[0] = !uip.unwrap();
+        // Second literal must be from the backtrack level
+        let mut level = 0;
+        for k in 1..learnt.len() {
+            if self.level[learnt[k].var()] > level {
+                level = self.level[learnt[k].var()];
+                learnt.swap(1,k);
+            }
+        }
+        (learnt,level)
+    }
+
+    fn bump_activity(&mut self, v: usize) {
+        self.activity[v] += self.bump;
+        if self.activity[v] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.bump *= 1e-100;
+        }
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.trail_lim.len() > level {
+            for lit in self.trail.drain(self.trail_lim[level]..) {
+                self.assigns[lit.var()] = None;
+                self.polarity[lit.var()] = lit.is_positive();
+            }
+            self.trail_lim.truncate(level);
+            self.qhead = self.trail.len();
+        }
+    }
+
+    /// Pick the unassigned variable with highest activity (if any).
+    fn pick(&self) -> Option<usize> {
+        let mut best : Option<usize> = None;
+        for v in 0..self.num_vars() {
+            if self.assigns[v].is_none() && best.is_none_or(|b| self.activity[v] > self.activity[b]) {
+                best = Some(v);
+            }
+        }
+        best
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compute the `i`th element of the Luby sequence (i.e. 1, 1, 2, 1, 1,
+/// 2, 4, 1, ...).
+fn luby(mut i: usize) -> usize {
+    let (mut size, mut seq) = (1, 0);
+    while size < i + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        i %= size;
+    }
+    1 << seq
+}
@@ -0,0 +1,457 @@
+use std::collections::{BTreeMap,HashMap,HashSet};
+use std::fmt;
+use crate::{BinaryOp,BitVec,MachineWord,Term,TermKind,UnaryOp};
+
+// ===================================================================
+// Script
+// ===================================================================
+
+/// An SMT-LIB 2 script over the theory of fixed-size bitvectors
+/// (`QF_BV`).  Terms are written such that each compound subterm is
+/// defined exactly once (using `define-fun`), thereby preserving the
+/// sharing present in hash-consed terms.  Since variables are
+/// identified by both their name and width, each is written as a
+/// symbol which encodes both (e.g. `x@8`), whilst definitions are
+/// written as symbols which cannot clash with these (e.g. `%t2`).
+/// Since the semantics of `Term` differ from SMT-LIB in places (e.g.
+/// division by zero, or comparisons which produce words), these are
+/// encoded explicitly.
+pub struct Script {
+    out: String,
+    /// Identifiers of terms already declared (or defined).
+    written: HashSet<usize>
+}
+
+impl Script {
+    /// Construct a script which sets the logic and enables model
+    /// generation.
+    pub fn new() -> Self {
+        let out = "(set-logic QF_BV)\n(set-option :produce-models true)\n".to_string();
+        Self{out, written: HashSet::new()}
+    }
+
+    /// Assert that a given 1bit term holds.
+    pub fn assert(&mut self, t: &Term) {
+        assert_eq!(t.width(), 1, "assertion must be 1bit");
+        let e = self.define(t);
+        self.out.push_str(&format!("(assert (= {} #b1))\n", e));
+    }
+
+    /// Request a satisfiability check.
+    pub fn check_sat(&mut self) {
+        self.out.push_str("(check-sat)\n");
+    }
+
+    /// Request a model (following a satisfiable check).
+    pub fn get_model(&mut self) {
+        self.out.push_str("(get-model)\n");
+    }
+
+    /// Ensure a given term (and all its subterms) are declared or
+    /// defined, returning an expression which refers to it.
+    pub fn define(&mut self, t: &Term) -> String {
+        match t.kind() {
+            TermKind::Const(v) => constant(v),
+            TermKind::Var(n) => {
+                if self.written.insert(t.id()) {
+                    self.out.push_str(&format!("(declare-const {} {})\n", symbol(n, t.width()), sort(t.width())));
+                }
+                symbol(n, t.width())
+            }
+            _ => {
+                // Definitions are named without a width
+                let name = format!("|%t{}|", t.id());
+                if !self.written.contains(&t.id()) {
+                    let e = self.expression(t);
+                    self.written.insert(t.id());
+                    self.out.push_str(&format!("(define-fun {} () {} {})\n", name, sort(t.width()), e));
+                }
+                name
+            }
+        }
+    }
+
+    /// Translate a compound term into an SMT-LIB expression, after
+    /// defining its operands.
+    fn expression(&mut self, t: &Term) -> String {
+        let w = t.width();
+        match t.kind() {
+            TermKind::Unary(op,a) => {
+                let a_w = a.width();
+                let a = self.define(a);
+                match op {
+                    UnaryOp::Neg => format!("(bvneg {})", a),
+                    UnaryOp::Not => format!("(bvnot {})", a),
+                    UnaryOp::NonZero => format!("(ite (= {} {}) #b0 #b1)", a, zero(a_w))
+                }
+            }
+            TermKind::Binary(op,l,r) => {
+                let l = self.define(l);
+                let r = self.define(r);
+                match op {
+                    BinaryOp::LessThan => format!("(ite (bvult {} {}) {} {})", l, r, one(w), zero(w)),
+                    BinaryOp::Equal => format!("(ite (= {} {}) {} {})", l, r, one(w), zero(w)),
+                    BinaryOp::Add => format!("(bvadd {} {})", l, r),
+                    BinaryOp::Mul => format!("(bvmul {} {})", l, r),
+                    // Division (and remainder) by zero gives zero
+                    BinaryOp::Div => format!("(ite (= {} {}) {} (bvudiv {} {}))", r, zero(w), zero(w), l, r),
+                    BinaryOp::Rem => format!("(ite (= {} {}) {} (bvurem {} {}))", r, zero(w), zero(w), l, r),
+                    BinaryOp::And => format!("(bvand {} {})", l, r),
+                    BinaryOp::Or => format!("(bvor {} {})", l, r),
+                    BinaryOp::Xor => format!("(bvxor {} {})", l, r)
+                }
+            }
+            TermKind::Ite(c,l,r) => {
+                let c = self.define(c);
+                let l = self.define(l);
+                let r = self.define(r);
+                format!("(ite (= {} #b1) {} {})", c, l, r)
+            }
+            _ => unreachable!()
+        }
+    }
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.out)
+    }
+}
+
+/// Construct a complete script for checking the validity of a given
+/// (1bit) verification condition.  Specifically, the script asserts
+/// that the condition does not hold, such that `unsat` indicates it
+/// is valid and, otherwise, the model is a counterexample.
+pub fn validity_query(vc: &Term) -> String {
+    let mut script = Script::new();
+    script.assert(&vc.clone().nonzero().not());
+    script.check_sat();
+    script.get_model();
+    script.to_string()
+}
+
+fn sort(width: usize) -> String {
+    format!("(_ BitVec {})", width)
+}
+
+fn zero(width: usize) -> String {
+    constant(&BitVec::zero(width))
+}
+
+fn one(width: usize) -> String {
+    constant(&BitVec::from_u64(width,1))
+}
+
+/// Write a bitvector constant in binary (or hexadecimal when the
+/// width permits).
+fn constant(v: &BitVec) -> String {
+    let w = v.width();
+    if w.is_multiple_of(4) {
+        let digits : String = (0..w/4).rev().map(|i| {
+            let d = (0..4).fold(0, |d,j| d | ((v.bit(4*i+j) as u32) << j));
+            char::from_digit(d,16).unwrap()
+        }).collect();
+        format!("#x{}", digits)
+    } else {
+        let digits : String = (0..w).rev().map(|i| if v.bit(i) {'1'} else {'0'}).collect();
+        format!("#b{}", digits)
+    }
+}
+
+/// Write a variable of a given name and width as an SMT-LIB symbol,
+/// quoting it when necessary.  The symbol is the (escaped) name
+/// followed by `@` and the width, such that distinct variables are
+/// written as distinct symbols.
+fn symbol(name: &str, width: usize) -> String {
+    let mut s = String::new();
+    for c in name.chars() {
+        match c {
+            '%' => s.push_str("%25"),
+            '\\' => s.push_str("%5c"),
+            '|' => s.push_str("%7c"),
+            _ => s.push(c)
+        }
+    }
+    let s = format!("{}@{}", s, width);
+    let simple = !s.starts_with(|c:char| c.is_ascii_digit())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    if simple { s } else { format!("|{}|", s) }
+}
+
+/// Recover the name of a variable of a given width from its symbol
+/// (as written by `symbol()`).  Symbols not of this form are taken
+/// to be the name itself.
+fn unsymbol(symbol: &str, width: usize) -> String {
+    let symbol = symbol.trim_matches('|');
+    match symbol.rsplit_once('@') {
+        Some((name,w)) if w.parse() == Ok(width) => {
+            name.replace("%7c", "|").replace("%5c", "\\").replace("%25", "%")
+        }
+        _ => symbol.to_string()
+    }
+}
+
+// ===================================================================
+// Models
+// ===================================================================
+
+/// An assignment of values to variables, as returned by a solver.
+/// Variables are identified by both their name and width.
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct Model {
+    values: BTreeMap<(String,usize),BitVec>
+}
+
+impl Model {
+    /// Construct an empty model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign a value to a given variable (whose width is that of the
+    /// value).
+    pub fn insert(&mut self, name: &str, value: BitVec) {
+        self.values.insert((name.to_string(),value.width()), value);
+    }
+
+    /// Get the value assigned to a given variable (if any).
+    pub fn get(&self, name: &str, width: usize) -> Option<&BitVec> {
+        self.values.get(&(name.to_string(),width))
+    }
+
+    /// Iterate the assignments in this model.
+    pub fn iter(&self) -> impl Iterator<Item=(&String,&BitVec)> {
+        self.values.iter().map(|((n,_),v)| (n,v))
+    }
+
+    /// Evaluate a given term under this model.  Solvers typically
+    /// omit variables whose value is irrelevant and, hence, any
+    /// variable not assigned in this model is taken to be zero.
+    pub fn eval(&self, t: &Term) -> BitVec {
+        let mut map = HashMap::new();
+        for v in variables(t) {
+            let TermKind::Var(n) = v.kind() else { unreachable!() };
+            let value = self.values.get(&(n.clone(),v.width())).cloned();
+            let value = value.unwrap_or_else(|| BitVec::zero(v.width()));
+            map.insert(v, Term::constant(value));
+        }
+        t.substitute(&map).as_constant().unwrap().clone()
+    }
+}
+
+/// Determine the variables of a given term.
+fn variables(t: &Term) -> HashSet<Term> {
+    fn find(t: &Term, vars: &mut HashSet<Term>, seen: &mut HashSet<usize>) {
+        if !seen.insert(t.id()) { return; }
+        match t.kind() {
+            TermKind::Var(_) => { vars.insert(t.clone()); }
+            TermKind::Const(_) => {}
+            TermKind::Unary(_,a) => find(a,vars,seen),
+            TermKind::Binary(_,l,r) => { find(l,vars,seen); find(r,vars,seen); }
+            TermKind::Ite(c,l,r) => { find(c,vars,seen); find(l,vars,seen); find(r,vars,seen); }
+        }
+    }
+    let mut vars = HashSet::new();
+    find(t, &mut vars, &mut HashSet::new());
+    vars
+}
+
+// ===================================================================
+// Responses
+// ===================================================================
+
+/// Errors which can arise when parsing the response of a solver.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum SmtError {
+    /// The response ended unexpectedly.
+    UnexpectedEnd,
+    /// An unexpected token was encountered.
+    UnexpectedToken(String),
+    /// A value was encountered which is not a bitvector literal.
+    InvalidValue(String),
+    /// The solver reported an error.
+    SolverError(String)
+}
+
+impl fmt::Display for SmtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for SmtError {
+
+}
+
+/// The response of a solver to a script produced by
+/// `validity_query()`.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum Response {
+    /// The assertions are satisfiable, with the given model.
+    Sat(Model),
+    /// The assertions are unsatisfiable.
+    Unsat,
+    /// The solver could not decide satisfiability.
+    Unknown
+}
+
+/// Parse the response of a solver to a `check-sat` command, followed
+/// (when satisfiable) by a `get-model` command.  Both the SMT-LIB 2.6
+/// format for models and the older `(model ...)` format are accepted.
+pub fn parse_response(input: &str) -> Result<Response,SmtError> {
+    let exprs = parse_sexprs(input)?;
+    let mut iter = exprs.iter();
+    match iter.next() {
+        Some(SExpr::Atom(s)) if s == "unsat" => Ok(Response::Unsat),
+        Some(SExpr::Atom(s)) if s == "unknown" => Ok(Response::Unknown),
+        Some(SExpr::Atom(s)) if s == "sat" => {
+            let mut model = Model::new();
+            if let Some(m) = iter.next() {
+                parse_model(m, &mut model)?;
+            }
+            Ok(Response::Sat(model))
+        }
+        Some(SExpr::List(l)) if matches!(l.first(), Some(SExpr::Atom(s)) if s == "error") => {
+            Err(SmtError::SolverError(format!("{}", exprs[0])))
+        }
+        Some(e) => Err(SmtError::UnexpectedToken(e.to_string())),
+        None => Err(SmtError::UnexpectedEnd)
+    }
+}
+
+fn parse_model(e: &SExpr, model: &mut Model) -> Result<(),SmtError> {
+    let items = match e {
+        SExpr::List(items) => items,
+        _ => { return Err(SmtError::UnexpectedToken(e.to_string())); }
+    };
+    for item in items {
+        match item {
+            // Older format
+            SExpr::Atom(s) if s == "model" => {}
+            SExpr::List(l) => {
+                match l.as_slice() {
+                    [SExpr::Atom(d), SExpr::Atom(name), SExpr::List(params), _, value]
+                        if d == "define-fun" && params.is_empty() => {
+                        let value = parse_value(value)?;
+                        model.insert(&unsymbol(name, value.width()), value);
+                    }
+                    _ => { return Err(SmtError::UnexpectedToken(item.to_string())); }
+                }
+            }
+            _ => { return Err(SmtError::UnexpectedToken(item.to_string())); }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a bitvector literal (e.g. `#x1f`, `#b101` or `(_ bv31 8)`).
+fn parse_value(e: &SExpr) -> Result<BitVec,SmtError> {
+    let invalid = || SmtError::InvalidValue(e.to_string());
+    match e {
+        SExpr::Atom(s) if s.starts_with("#x") || s.starts_with("#b") => {
+            let (radix,bits) = if s.starts_with("#x") { (16,4) } else { (2,1) };
+            let digits = &s[2..];
+            if digits.is_empty() { return Err(invalid()); }
+            let mut v = BitVec::zero(digits.len() * bits);
+            for (i,c) in digits.chars().rev().enumerate() {
+                let d = c.to_digit(radix).ok_or_else(invalid)?;
+                (0..bits).for_each(|j| v.set_bit(i*bits+j, (d >> j) & 1 == 1));
+            }
+            Ok(v)
+        }
+        SExpr::List(l) => {
+            match l.as_slice() {
+                [SExpr::Atom(u), SExpr::Atom(n), SExpr::Atom(w)] if u == "_" && n.starts_with("bv") => {
+                    let w : usize = w.parse().map_err(|_| invalid())?;
+                    if w == 0 { return Err(invalid()); }
+                    // Decimal digits are accumulated using bitvector
+                    // arithmetic, so arbitrary widths are supported.
+                    let ten = BitVec::from_u64(w,10);
+                    let mut v = BitVec::zero(w);
+                    for c in n[2..].chars() {
+                        let d = c.to_digit(10).ok_or_else(invalid)?;
+                        v = v.overflowing_mul(&ten).0.overflowing_add(&BitVec::from_u64(w,d as u64)).0;
+                    }
+                    Ok(v)
+                }
+                _ => Err(invalid())
+            }
+        }
+        _ => Err(invalid())
+    }
+}
+
+// ===================================================================
+// S-Expressions
+// ===================================================================
+
+/// A minimal representation of S-expressions.
+#[derive(Clone,Debug,PartialEq,Eq)]
+enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>)
+}
+
+impl fmt::Display for SExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SExpr::Atom(s) => write!(f, "{}", s),
+            SExpr::List(l) => {
+                write!(f, "(")?;
+                for (i,e) in l.iter().enumerate() {
+                    if i != 0 { write!(f, " ")?; }
+                    write!(f, "{}", e)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Parse a sequence of S-expressions.
+fn parse_sexprs(input: &str) -> Result<Vec<SExpr>,SmtError> {
+    let mut stack : Vec<Vec<SExpr>> = vec![Vec::new()];
+    let chars : Vec<char> = input.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => { i += 1; }
+            ';' => {
+                // Skip comment
+                while i < chars.len() && chars[i] != '\n' { i += 1; }
+            }
+            '(' => {
+                stack.push(Vec::new());
+                i += 1;
+            }
+            ')' => {
+                if stack.len() == 1 { return Err(SmtError::UnexpectedToken(")".to_string())); }
+                let l = stack.pop().unwrap();
+                stack.last_mut().unwrap().push(SExpr::List(l));
+                i += 1;
+            }
+            c => {
+                let start = i;
+                if c == '|' || c == '"' {
+                    // Quoted symbol or string
+                    i += 1;
+                    while i < chars.len() && chars[i] != c { i += 1; }
+                    if i == chars.len() { return Err(SmtError::UnexpectedEnd); }
+                    i += 1;
+                } else {
+                    while i < chars.len() && !chars[i].is_whitespace() && !"();".contains(chars[i]) { i += 1; }
+                }
+                let atom : String = chars[start..i].iter().collect();
+                stack.last_mut().unwrap().push(SExpr::Atom(atom));
+            }
+        }
+    }
+    if stack.len() != 1 { return Err(SmtError::UnexpectedEnd); }
+    Ok(stack.pop().unwrap())
+}
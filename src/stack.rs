@@ -0,0 +1,353 @@
+use std::marker::PhantomData;
+use crate::{BitVec,Machine,MachineError,MachineState,MachineWord,MinimalMachineError,StepMachine,StepOutcome};
+use crate::{CallState,ConcreteStorage,Frame,Memory,MemoryState,Storage,StorageState,Term,VecState,Verifiable,WordMemory};
+
+// ===================================================================
+// Machine definition
+// ===================================================================
+
+/// A non-trivial, yet minimalistic machine implementation.  This can
+/// describe non-trivial computation and is primarily based around a
+/// stack machine.  The semantics are given purely in terms of
+/// `MachineWord` and, hence, the same machine can be executed over
+/// concrete words (e.g. `u8`), abstract words (e.g. `Interval`) or
+/// symbolic words (e.g. `Term`).  Likewise, the memory (and storage)
+/// of the machine is given by any suitable `Memory` (and `Storage`)
+/// over those words.
+pub struct StackMachine<T:MachineWord,M=WordMemory<T,MinimalMachineError>,S=ConcreteStorage<T,MinimalMachineError>> {
+    dummy: PhantomData<(T,M,S)>,
+    code: Vec<Bytecode<T>>
+}
+
+impl<T:MachineWord,M,S> StackMachine<T,M,S> {
+    pub fn new(code: Vec<Bytecode<T>>) -> Self {
+        Self{code,dummy: PhantomData}
+    }
+}
+
+// ===================================================================
+// Bytecodes
+// ===================================================================
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Bytecode<T> {
+    // Literals
+    Push(T),
+    // Stack
+    /// Push a copy of the `nth` item on the stack (where `n==0` is the
+    /// top).
+    Dup(usize),
+    /// Swap the top item on the stack with the `nth` item (where
+    /// `n>0`).
+    Swap(usize),
+    /// Discard the top item on the stack.
+    Pop,
+    // Comparators
+    Eq,
+    Neq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    // Arithmetic
+    Add,
+    Sub,
+    Mul,
+    /// Division, which fails when the divisor is zero.
+    Div,
+    /// Remainder, which fails when the divisor is zero.
+    Rem,
+    // Memory
+    /// Pop an address and push the word formed from the given number
+    /// of bytes in memory starting at that address.
+    MLoad(usize),
+    /// Pop an address and then a value, and store the given number of
+    /// (least significant) bytes of the value in memory starting at
+    /// that address.
+    MStore(usize),
+    // Storage
+    /// Pop a key and push the value associated with it in storage.
+    SLoad,
+    /// Pop a key and then a value, and associate the value with the
+    /// key in storage.
+    SStore,
+    // Assertions
+    /// Pop the top of the stack, which must be non-zero.
+    Assert,
+    /// An undefined opcode, whose execution always fails.
+    Invalid(u8),
+    // Control-Flow
+    /// Continue at a given position.
+    Jump(usize),
+    /// Pop the top of the stack and, if it is non-zero, continue at a
+    /// given position (otherwise, at the next instruction).
+    JumpIf(usize),
+    /// Terminate normally without producing a value.
+    Halt,
+    /// Pop the top of the stack and terminate abnormally with it.
+    /// Within a subroutine, instead roll back all stores it made and
+    /// return from it (leaving the stack as is).
+    Revert,
+    /// Pop the top of the stack and terminate normally with it.
+    Return,
+    // Subroutines
+    /// Pop a given number of arguments into the local slots of a new
+    /// frame (with the first argument pushed in slot `0`), and
+    /// continue at a given position.  On return, execution continues
+    /// at the next instruction.
+    Call(usize,usize),
+    /// Pop the current frame and continue at its return position.
+    Ret,
+    /// Push a copy of the `nth` local slot of the current frame.
+    Local(usize),
+    /// Pop the top of the stack into the `nth` local slot of the
+    /// current frame.
+    SetLocal(usize)
+}
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum RuntimeOutput<T> {
+    Error,
+    Value(T)
+}
+
+// ===================================================================
+// Semantics
+// ===================================================================
+
+/// Each call to `execute()` executes exactly one instruction.  When
+/// the condition of a `JumpIf` is unknown (e.g. for abstract or
+/// symbolic words), execution forks into both successors, with the
+/// fall through state first.  Likewise, faults (e.g. division by
+/// zero) are only raised when they definitely occur.  Otherwise,
+/// execution proceeds and the corresponding obligations are
+/// identified via `Verifiable`.
+impl<T:MachineWord+Clone,M,S> Machine for StackMachine<T,M,S>
+where M: Memory<Word=T,Error=MinimalMachineError>+Clone,
+      S: Storage<Word=T,Error=MinimalMachineError>+Clone {
+    type Error = MinimalMachineError;
+    type State = VecState<T,Self::Error,M,S>;
+    type Instruction = Bytecode<T>;
+    type Outcome = StepOutcome<Self::State,T>;
+
+    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        if pc < self.code.len() {
+            Ok(&self.code[pc])
+        } else {
+            Err(Self::Error::InvalidPC)
+        }
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        let pc = state.pc();
+        let r = match self.get(pc)? {
+            // Literals
+            Bytecode::Push(c) => c.clone(),
+            // Stack
+            Bytecode::Dup(n) => state.peek(*n)?.clone(),
+            Bytecode::Swap(n) => {
+                state.swap(*n)?;
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::Pop => {
+                state.pop()?;
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+            // Comparators
+            Bytecode::Eq => binary(&mut state, |l,r| l.equal(r))?,
+            Bytecode::Neq => binary(&mut state, |l,r| l.clone().less_than(r.clone()).or(r.less_than(l)))?,
+            Bytecode::Lt => binary(&mut state, |l,r| l.less_than(r))?,
+            Bytecode::LtEq => binary(&mut state, |l,r| l.clone().less_than(r.clone()).or(l.equal(r)))?,
+            Bytecode::Gt => binary(&mut state, |l,r| r.less_than(l))?,
+            Bytecode::GtEq => binary(&mut state, |l,r| r.clone().less_than(l.clone()).or(l.equal(r)))?,
+            // Arithmetic
+            Bytecode::Add => binary(&mut state, |l,r| l.add(r))?,
+            Bytecode::Sub => binary(&mut state, |l,r| l.sub(r))?,
+            Bytecode::Mul => binary(&mut state, |l,r| l.mul(r))?,
+            Bytecode::Div => {
+                check_divisor(&state)?;
+                binary(&mut state, |l,r| l.div(r))?
+            }
+            Bytecode::Rem => {
+                check_divisor(&state)?;
+                binary(&mut state, |l,r| l.rem(r))?
+            }
+            // Memory
+            Bytecode::MLoad(n) => {
+                let address = state.pop()?;
+                state.memory_mut().load(&address, *n)?
+            }
+            Bytecode::MStore(n) => {
+                let address = state.pop()?;
+                let value = state.pop()?;
+                state.memory_mut().store(&address, *n, value)?;
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+            // Storage
+            Bytecode::SLoad => {
+                let key = state.pop()?;
+                state.storage().load(&key)?
+            }
+            Bytecode::SStore => {
+                let key = state.pop()?;
+                let value = state.pop()?;
+                state.storage_mut().store(key, value)?;
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+            // Assertions
+            Bytecode::Assert => {
+                if state.pop()?.to_bool() == Some(false) {
+                    return Err(Self::Error::assertion_failure());
+                }
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::Invalid(opcode) => { return Err(Self::Error::invalid_opcode(*opcode)); }
+            // Control-Flow
+            Bytecode::Jump(target) => {
+                self.check_target(*target)?;
+                state.goto(*target);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::JumpIf(target) => {
+                let c = state.pop()?;
+                if c.to_bool() != Some(false) {
+                    self.check_target(*target)?;
+                }
+                let mut taken = state.clone();
+                taken.goto(*target);
+                state.goto(pc+1);
+                return Ok(match c.to_bool() {
+                    Some(false) => StepOutcome::Continue(state),
+                    Some(true) => StepOutcome::Continue(taken),
+                    // Both successors coincide
+                    None if *target == pc+1 => StepOutcome::Continue(state),
+                    None => StepOutcome::Fork(vec![state,taken])
+                });
+            }
+            Bytecode::Halt => { return Ok(StepOutcome::Halt); }
+            Bytecode::Revert if state.depth() > 0 => {
+                // Roll back to the checkpoint of the current frame
+                let d = state.depth();
+                state.storage_mut().revert(d - 1);
+                let frame = state.ret()?;
+                state.goto(frame.return_pc());
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::Revert => { return Ok(StepOutcome::Revert(state.pop()?)); }
+            Bytecode::Return => { return Ok(StepOutcome::Return(state.pop()?)); }
+            // Subroutines
+            Bytecode::Call(target,n) => {
+                self.check_target(*target)?;
+                let mut locals = (0..*n).map(|_| state.pop()).collect::<Result<Vec<_>,_>>()?;
+                locals.reverse();
+                state.call(Frame::new(pc+1, locals))?;
+                state.goto(*target);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::Ret => {
+                let frame = state.ret()?;
+                state.goto(frame.return_pc());
+                return Ok(StepOutcome::Continue(state));
+            }
+            Bytecode::Local(n) => {
+                state.frame().and_then(|f| f.get(*n)).cloned().ok_or_else(|| Self::Error::invalid_local(*n))?
+            }
+            Bytecode::SetLocal(n) => {
+                let item = state.pop()?;
+                state.frame_mut().and_then(|f| f.set(*n, item)).ok_or_else(|| Self::Error::invalid_local(*n))?;
+                state.goto(pc+1);
+                return Ok(StepOutcome::Continue(state));
+            }
+        };
+        state.push(r)?;
+        state.goto(pc+1);
+        Ok(StepOutcome::Continue(state))
+    }
+}
+
+impl<T:MachineWord+Clone,M,S> StepMachine for StackMachine<T,M,S>
+where M: Memory<Word=T,Error=MinimalMachineError>+Clone,
+      S: Storage<Word=T,Error=MinimalMachineError>+Clone {
+    type Value = T;
+}
+
+impl<T:MachineWord,M,S> StackMachine<T,M,S> {
+    /// Check a given jump destination is within the code section.
+    fn check_target(&self, target: usize) -> Result<(),MinimalMachineError> {
+        if target < self.code.len() {
+            Ok(())
+        } else {
+            Err(MinimalMachineError::invalid_jump(target))
+        }
+    }
+}
+
+/// Symbolic execution of the reference machine can be verified,
+/// where the obligations are that assertions hold, divisors are
+/// non-zero and memory accesses lie within the limit of memory.
+impl<M,S> Verifiable for StackMachine<Term,M,S>
+where M: Memory<Word=Term,Error=MinimalMachineError>+Clone,
+      S: Storage<Word=Term,Error=MinimalMachineError>+Clone {
+    fn obligation(&self, state: &Self::State) -> Option<Term> {
+        match self.get(state.pc()) {
+            Ok(Bytecode::Assert|Bytecode::Div|Bytecode::Rem) => state.peek(0).ok().cloned(),
+            Ok(Bytecode::MLoad(n)|Bytecode::MStore(n)) => {
+                let address = state.peek(0).ok()?;
+                Some(within_limit(address, *n, state.memory().limit()))
+            }
+            _ => None
+        }
+    }
+
+    fn accesses(&self, state: &Self::State) -> bool {
+        matches!(self.get(state.pc()), Ok(Bytecode::MLoad(_)|Bytecode::MStore(_)|Bytecode::SLoad|Bytecode::SStore))
+    }
+
+    fn guard(&self, state: &Self::State, successor: &Self::State) -> Term {
+        let c = state.peek(0).unwrap().clone();
+        if successor.pc() == state.pc() + 1 {
+            let w = c.width();
+            c.equal(Term::constant(BitVec::zero(w)))
+        } else {
+            c
+        }
+    }
+}
+
+/// Determine the condition under which accessing `n` bytes at a given
+/// address lies within a given limit (i.e. `address + n <= limit`).
+fn within_limit(address: &Term, n: usize, limit: usize) -> Term {
+    if n == 0 { return Term::bool(true); }
+    let Some(bound) = limit.checked_sub(n) else { return Term::bool(false); };
+    let b = address.constant_like(bound as u64);
+    if b.to_usize() == Some(bound) {
+        b.less_than(address.clone()).nonzero().not()
+    } else {
+        // Every address lies within the limit
+        Term::bool(true)
+    }
+}
+
+/// Check the divisor (i.e. top of the stack) is not definitely zero.
+fn check_divisor<S:MachineState>(state: &S) -> Result<(),S::Error>
+where S::Error: MachineError {
+    if state.peek(0)?.to_bool() == Some(false) {
+        Err(S::Error::division_by_zero())
+    } else {
+        Ok(())
+    }
+}
+
+/// Pop two operands (with the right-hand side on top) and apply a
+/// given operator to them.
+fn binary<S:MachineState,F>(state: &mut S, op: F) -> Result<S::Word,S::Error>
+where F: Fn(S::Word,S::Word)->S::Word {
+    let r = state.pop()?;
+    let l = state.pop()?;
+    Ok(op(l,r))
+}
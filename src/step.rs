@@ -0,0 +1,73 @@
+use crate::{Branch,Branching,Machine};
+
+/// A standard outcome for machines which execute exactly one
+/// instruction at a time.  Execution either continues with one (or
+/// more) states, or terminates (in some sense).
+#[derive(Clone,Debug,PartialEq)]
+pub enum StepOutcome<S,V> {
+    /// Execution continues with exactly one state (e.g. at the next
+    /// instruction, or the target of a jump).
+    Continue(S),
+    /// Execution forks into zero or more states (e.g. at a branch
+    /// whose condition is unknown).
+    Fork(Vec<S>),
+    /// Execution terminates normally, producing a given value.
+    Return(V),
+    /// Execution terminates abnormally, producing a given value.
+    Revert(V),
+    /// Execution terminates normally without producing a value.
+    Halt
+}
+
+/// The ways in which execution of a `StepMachine` can terminate.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Termination<V> {
+    /// Execution terminated normally, producing a given value.
+    Return(V),
+    /// Execution terminated abnormally, producing a given value.
+    Revert(V),
+    /// Execution terminated normally without producing a value.
+    Halt
+}
+
+impl<S,V> Branching<S> for StepOutcome<S,V> {
+    type Terminal = Termination<V>;
+
+    fn branch(self) -> Branch<S,Termination<V>> {
+        match self {
+            StepOutcome::Continue(s) => Branch::Continue(vec![s]),
+            StepOutcome::Fork(ss) => Branch::Continue(ss),
+            StepOutcome::Return(v) => Branch::Terminate(Termination::Return(v)),
+            StepOutcome::Revert(v) => Branch::Terminate(Termination::Revert(v)),
+            StepOutcome::Halt => Branch::Terminate(Termination::Halt)
+        }
+    }
+}
+
+/// A machine which executes exactly one instruction for each call to
+/// `execute()`.  This allows generic tools (e.g. debuggers, explorers
+/// and tracers) to interpose between instructions.
+pub trait StepMachine : Machine<Outcome=StepOutcome<<Self as Machine>::State,<Self as StepMachine>::Value>> {
+    /// Represents a value produced when execution terminates.
+    type Value;
+
+    /// Execute from a given state until execution either terminates
+    /// or forks, invoking a given observer on each state before it
+    /// is executed.
+    fn run_with<F>(&self, mut state: Self::State, mut observer: F) -> Result<Self::Outcome,Self::Error>
+    where F: FnMut(&Self::State) {
+        loop {
+            observer(&state);
+            match self.execute(state)? {
+                StepOutcome::Continue(s) => { state = s; }
+                outcome => { return Ok(outcome); }
+            }
+        }
+    }
+
+    /// Execute from a given state until execution either terminates
+    /// or forks.
+    fn run(&self, state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        self.run_with(state, |_| {})
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use crate::{Array,BitVec,Lattice,MachineError,MachineState,MachineWord,Merge,Term};
+
+// ===================================================================
+// Storage
+// ===================================================================
+
+/// A persistent map from words to words, where every key initially
+/// maps to zero.  Storage is _journaled_, such that all stores made
+/// after a given checkpoint can be rolled back (e.g. when a
+/// subroutine reverts).
+pub trait Storage {
+    /// Defines the words used for keys and values.
+    type Word : MachineWord;
+    /// Defines the set of possible errors which can be raised when
+    /// accessing storage.
+    type Error;
+
+    /// Load the value associated with a given key.
+    fn load(&self, key: &Self::Word) -> Result<Self::Word,Self::Error>;
+
+    /// Associate a given value with a given key.
+    fn store(&mut self, key: Self::Word, value: Self::Word) -> Result<(),Self::Error>;
+
+    /// Record a checkpoint to which this storage can later be
+    /// reverted.
+    fn checkpoint(&mut self) -> usize;
+
+    /// Roll back all stores made since a given checkpoint, which is
+    /// then discarded (along with any later checkpoints).
+    fn revert(&mut self, checkpoint: usize);
+
+    /// Discard a given checkpoint (along with any later checkpoints),
+    /// whilst keeping all stores made since.
+    fn commit(&mut self, checkpoint: usize);
+}
+
+/// A machine state which has persistent storage, in addition to its
+/// stack.
+pub trait StorageState : MachineState {
+    type Storage : Storage<Word=Self::Word,Error=Self::Error>;
+
+    /// Get the storage of this state.
+    fn storage(&self) -> &Self::Storage;
+
+    /// Get the storage of this state (mutably).
+    fn storage_mut(&mut self) -> &mut Self::Storage;
+}
+
+// ===================================================================
+// Concrete Storage
+// ===================================================================
+
+/// A storage whose keys must be known (failing with
+/// `invalid_address()` otherwise), but whose values may be abstract
+/// (or symbolic).  The journal records a snapshot of the entries at
+/// each checkpoint.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ConcreteStorage<T,E> {
+    dummy: PhantomData<E>,
+    entries: BTreeMap<BitVec,T>,
+    journal: Vec<BTreeMap<BitVec,T>>
+}
+
+impl<T,E> ConcreteStorage<T,E> {
+    pub fn new() -> Self {
+        Self{entries: BTreeMap::new(), journal: Vec::new(), dummy: PhantomData}
+    }
+
+    /// Get the value associated with a given key, if it has been
+    /// stored.
+    pub fn get(&self, key: &BitVec) -> Option<&T> {
+        self.entries.get(key)
+    }
+
+    /// Construct a new storage by applying a given function to each
+    /// value stored (including those in the journal).
+    pub fn map<U,F:Fn(&T)->U>(&self, f: F) -> ConcreteStorage<U,E> {
+        let g = |entries: &BTreeMap<BitVec,T>| entries.iter().map(|(k,v)| (k.clone(),f(v))).collect();
+        ConcreteStorage{entries: g(&self.entries), journal: self.journal.iter().map(g).collect(), dummy: PhantomData}
+    }
+}
+
+impl<T,E> Default for ConcreteStorage<T,E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T:MachineWord+Clone,E:MachineError> Storage for ConcreteStorage<T,E> {
+    type Word = T;
+    type Error = E;
+
+    fn load(&self, key: &T) -> Result<T,E> {
+        let k = key.to_bitvec().ok_or_else(E::invalid_address)?;
+        Ok(self.entries.get(&k).cloned().unwrap_or_else(|| key.constant_like(0)))
+    }
+
+    fn store(&mut self, key: T, value: T) -> Result<(),E> {
+        let k = key.to_bitvec().ok_or_else(E::invalid_address)?;
+        self.entries.insert(k, value);
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.journal.push(self.entries.clone());
+        self.journal.len() - 1
+    }
+
+    fn revert(&mut self, checkpoint: usize) {
+        if checkpoint < self.journal.len() {
+            self.entries = self.journal[checkpoint].clone();
+            self.journal.truncate(checkpoint);
+        }
+    }
+
+    fn commit(&mut self, checkpoint: usize) {
+        self.journal.truncate(checkpoint);
+    }
+}
+
+/// Storages are ordered pointwise over their entries, where keys not
+/// stored in one storage (but stored in the other) map to zero.  As
+/// for `WordMemory`, `top()` makes every value stored unknown.  The
+/// snapshots of their journals are likewise ordered pointwise and,
+/// hence, storages being compared must have the same number of
+/// checkpoints.
+impl<T:Lattice+MachineWord+Clone,E> Lattice for ConcreteStorage<T,E> {
+    fn bottom(&self) -> Self {
+        self.map(|v| v.bottom())
+    }
+
+    fn top(&self) -> Self {
+        self.map(|v| v.top())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.join(r))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.meet(r))
+    }
+
+    fn compatible(&self, other: &Self) -> bool {
+        self.journal.len() == other.journal.len()
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        let leq = |l,r| pairs(l, r).iter().all(|(_,l,r):&(BitVec,T,T)| l.leq(r));
+        leq(&self.entries, &other.entries)
+            && self.journal.iter().zip(other.journal.iter()).all(|(l,r)| leq(l, r))
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.widen(r))
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        self.zip(other, |l,r| l.narrow(r))
+    }
+}
+
+/// Symbolic storages are merged pointwise over their entries (and
+/// the snapshots of their journals), provided they have the same
+/// number of checkpoints.
+impl<E> Merge for ConcreteStorage<Term,E> {
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self> {
+        if lhs.journal.len() == rhs.journal.len() {
+            Some(lhs.zip(rhs, |l,r| Term::ite(cond.clone(), l.clone(), r.clone())))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T:MachineWord+Clone,E> ConcreteStorage<T,E> {
+    fn zip<F:Fn(&T,&T)->T>(&self, other: &Self, f: F) -> Self {
+        let g = |l,r| pairs(l, r).into_iter().map(|(k,l,r)| (k,f(&l,&r))).collect();
+        let journal = self.journal.iter().zip(other.journal.iter()).map(|(l,r)| g(l, r)).collect();
+        Self{entries: g(&self.entries, &other.entries), journal, dummy: PhantomData}
+    }
+}
+
+/// Pair up the values held in either of two sets of entries, by key.
+fn pairs<T:MachineWord+Clone>(lhs: &BTreeMap<BitVec,T>, rhs: &BTreeMap<BitVec,T>) -> Vec<(BitVec,T,T)> {
+    let mut keys : Vec<&BitVec> = lhs.keys().chain(rhs.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter().map(|k| {
+        let (l,r) = (lhs.get(k),rhs.get(k));
+        let zero = l.or(r).unwrap().constant_like(0);
+        (k.clone(), l.cloned().unwrap_or_else(|| zero.clone()), r.cloned().unwrap_or(zero))
+    }).collect()
+}
+
+// ===================================================================
+// Symbolic Storage
+// ===================================================================
+
+/// A storage whose keys and values may both be symbolic, where
+/// entries are held in an `Array`.  The journal records a snapshot of
+/// the array at each checkpoint.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct SymbolicStorage<E> {
+    dummy: PhantomData<E>,
+    entries: Array,
+    journal: Vec<Array>
+}
+
+impl<E> SymbolicStorage<E> {
+    pub fn new() -> Self {
+        Self{entries: Array::new(), journal: Vec::new(), dummy: PhantomData}
+    }
+
+    /// Get the array holding the entries of this storage.
+    pub fn entries(&self) -> &Array {
+        &self.entries
+    }
+}
+
+impl<E:MachineError> Storage for SymbolicStorage<E> {
+    type Word = Term;
+    type Error = E;
+
+    fn load(&self, key: &Term) -> Result<Term,E> {
+        Ok(self.entries.select(key))
+    }
+
+    fn store(&mut self, key: Term, value: Term) -> Result<(),E> {
+        self.entries.store(key, value);
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.journal.push(self.entries.clone());
+        self.journal.len() - 1
+    }
+
+    fn revert(&mut self, checkpoint: usize) {
+        if checkpoint < self.journal.len() {
+            self.entries = self.journal[checkpoint].clone();
+            self.journal.truncate(checkpoint);
+        }
+    }
+
+    fn commit(&mut self, checkpoint: usize) {
+        self.journal.truncate(checkpoint);
+    }
+}
+
+/// Symbolic storages are merged elementwise over their arrays (and
+/// the snapshots of their journals), provided they have the same
+/// number of checkpoints.
+impl<E> Merge for SymbolicStorage<E> {
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self> {
+        if lhs.journal.len() == rhs.journal.len() {
+            let entries = Array::merge(cond, &lhs.entries, &rhs.entries);
+            let journal = lhs.journal.iter().zip(rhs.journal.iter()).map(|(l,r)| Array::merge(cond, l, r)).collect();
+            Some(Self{entries, journal, dummy: PhantomData})
+        } else {
+            None
+        }
+    }
+}
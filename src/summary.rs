@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap,HashMap};
+use crate::{CallState,Frame,GasState,MachineState,MachineWord,Merge,Term,TermKind,VcError,Verifiable};
+use crate::vcgen::{merge_all,successors};
+
+// ===================================================================
+// Summaries
+// ===================================================================
+
+/// A summary of a subroutine, given by its entry position and stack
+/// effect, along with a precondition and postcondition.  On entry,
+/// the subroutine's arguments are held in its local slots (i.e. as
+/// for `CallState`) and, on return, it has pushed its results onto
+/// an otherwise empty stack.  The arguments and results are
+/// represented by distinct variables, such that the precondition is
+/// a 1bit term over the arguments, and the postcondition a 1bit term
+/// over both.
+///
+/// Summaries describe only the stack effect of a subroutine and,
+/// hence, subroutines must not access memory (or storage).  Likewise,
+/// the gas remaining after a call is unknown, except that it does
+/// not exceed that beforehand.  Instead, a summary may bound the gas
+/// required on entry to the subroutine (by default, none).  Variables
+/// introduced for the results of calls (and the gas remaining) are
+/// named using the prefix `%` which, hence, is reserved for them.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Summary {
+    entry: usize,
+    params: Vec<Term>,
+    results: Vec<Term>,
+    requires: Term,
+    ensures: Term,
+    gas: Option<Term>
+}
+
+impl Summary {
+    /// Construct a summary for the subroutine at a given position,
+    /// which accepts arguments and produces results represented by
+    /// given variables.  Initially, both the precondition and
+    /// postcondition are trivially true.
+    pub fn new(entry: usize, params: Vec<Term>, results: Vec<Term>) -> Self {
+        for v in params.iter().chain(results.iter()) {
+            assert!(matches!(v.kind(), TermKind::Var(_)), "summary parameters must be variables");
+        }
+        Self{entry, params, results, requires: Term::bool(true), ensures: Term::bool(true), gas: None}
+    }
+
+    /// Set the precondition of this summary.
+    pub fn requires(mut self, pre: Term) -> Self {
+        assert_eq!(pre.width(), 1, "precondition must be 1bit");
+        self.requires = pre;
+        self
+    }
+
+    /// Set the postcondition of this summary.
+    pub fn ensures(mut self, post: Term) -> Self {
+        assert_eq!(post.width(), 1, "postcondition must be 1bit");
+        self.ensures = post;
+        self
+    }
+
+    /// Set the gas required on entry to the subroutine, as a term over
+    /// the arguments.
+    pub fn gas(mut self, gas: Term) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    /// Get the entry position of the summarised subroutine.
+    pub fn entry(&self) -> usize { self.entry }
+
+    /// Get the variables representing the arguments.
+    pub fn params(&self) -> &[Term] { &self.params }
+
+    /// Get the variables representing the results.
+    pub fn results(&self) -> &[Term] { &self.results }
+}
+
+// ===================================================================
+// Modular VC Generation
+// ===================================================================
+
+/// Generate a single verification condition for a program composed
+/// of summarised subroutines, starting from a given (symbolic)
+/// initial state.  The condition holds when both the program and the
+/// body of each summarised subroutine are correct, where every call
+/// to a summarised subroutine is replaced by its summary.  That is,
+/// a call must establish the precondition, after which the results
+/// are fresh variables constrained only by the postcondition.
+/// Likewise, the body of each subroutine is verified once, assuming
+/// its precondition, and must establish its postcondition on return.
+/// Since a subroutine is entered with an unknown state, its body must
+/// not access memory (or storage) and, when execution is metered, is
+/// verified for any gas which meets the bound given by its summary.
+///
+/// Since the program (and each body) is verified as for
+/// `generate_vc()`, each must be acyclic once calls are summarised.
+/// However, subroutines may call themselves (or one another).  An
+/// error is returned if the body of a subroutine accesses memory (or
+/// storage).
+pub fn generate_modular_vc<M>(machine: &M, init: M::State, summaries: &[Summary]) -> Result<Term,VcError>
+where M: Verifiable, M::State: CallState<Word=Term>+GasState+Merge+Clone {
+    let summaries : BTreeMap<usize,&Summary> = summaries.iter().map(|s| (s.entry,s)).collect();
+    let mut vcg = Modular{machine, summaries, fresh: 0};
+    let mut vc = vcg.check(Term::bool(true), init.clone(), None)?;
+    for s in vcg.summaries.clone().into_values() {
+        // Enter the subroutine with only its arguments
+        let mut entry = init.clone();
+        while entry.pop().is_ok() {}
+        while entry.ret().is_ok() {}
+        entry.goto(s.entry);
+        if entry.call(Frame::new(s.entry, s.params.clone())).is_err() {
+            return Ok(Term::bool(false));
+        }
+        // Enter the subroutine with any gas meeting its bound
+        let mut pre = s.requires.clone();
+        if let Some(gas) = entry.gas() {
+            vcg.fresh += 1;
+            let g = Term::var(&format!("%gas.{}", vcg.fresh), gas.width());
+            pre = pre.and(Modular::<M>::sufficient(s, &g, &HashMap::new()));
+            entry.set_gas(g);
+        }
+        vc = vc.and(vcg.check(pre, entry, Some(s))?);
+    }
+    Ok(vc)
+}
+
+struct Modular<'a,M> {
+    machine: &'a M,
+    summaries: BTreeMap<usize,&'a Summary>,
+    /// Number of fresh variables introduced thus far, used to name
+    /// them.
+    fresh: usize
+}
+
+impl<'a,M:Verifiable> Modular<'a,M>
+where M::State: CallState<Word=Term>+GasState+Merge+Clone {
+    /// Generate the verification condition from a given state, which
+    /// is either the initial state of the program or the entry state
+    /// of a given subroutine.
+    fn check(&mut self, pre: Term, init: M::State, body: Option<&Summary>) -> Result<Term,VcError> {
+        let depth = init.depth();
+        let mut pending : BTreeMap<usize,Vec<(Term,M::State)>> = BTreeMap::new();
+        let mut vc = Term::bool(true);
+        pending.insert(init.pc(), vec![(pre,init)]);
+        //
+        while let Some((pc,states)) = pending.pop_first() {
+            for (guard,state) in merge_all(states) {
+                if body.is_some() && self.machine.accesses(&state) {
+                    return Err(VcError::SummaryEffect{pc});
+                }
+                let d = state.depth();
+                for (mut g,mut s) in successors(self.machine, guard, state, &mut vc) {
+                    if s.depth() > d {
+                        if let Some(callee) = self.summaries.get(&s.pc()).copied() {
+                            // Replace call with summary
+                            let Ok(frame) = s.ret() else { unreachable!() };
+                            if frame.locals().len() != callee.params.len() {
+                                return Err(VcError::SummaryMismatch{pc});
+                            }
+                            match self.call(callee, frame.locals(), &mut s) {
+                                Some((pre,post)) => {
+                                    vc = vc.and(g.clone().implies(pre));
+                                    g = g.and(post);
+                                }
+                                None => { vc = vc.and(g.not()); continue; }
+                            }
+                            s.goto(frame.return_pc());
+                        }
+                    } else if let Some(summary) = body.filter(|_| s.depth() < depth) {
+                        // Returned from subroutine
+                        vc = vc.and(g.implies(Self::ret(summary, &s)));
+                        continue;
+                    }
+                    if s.pc() <= pc {
+                        return Err(VcError::BackwardsJump{from: pc, to: s.pc()});
+                    }
+                    pending.entry(s.pc()).or_default().push((g,s));
+                }
+            }
+        }
+        Ok(vc)
+    }
+
+    /// Push fresh results for a call to a given subroutine with given
+    /// arguments, returning its (instantiated) precondition and
+    /// postcondition.  The gas remaining (if metered) must meet the
+    /// bound of the summary, and is replaced by a fresh variable which
+    /// the postcondition bounds by that beforehand.  If the results
+    /// cannot be pushed, then `None` is returned.
+    fn call(&mut self, callee: &Summary, args: &[Term], state: &mut M::State) -> Option<(Term,Term)> {
+        self.fresh += 1;
+        let mut map : HashMap<Term,Term> = callee.params.iter().cloned().zip(args.iter().cloned()).collect();
+        let mut pre = callee.requires.substitute(&map);
+        for r in &callee.results {
+            let TermKind::Var(name) = r.kind() else { unreachable!() };
+            let v = Term::var(&format!("%{}.{}", name, self.fresh), r.width());
+            state.push(v.clone()).ok()?;
+            map.insert(r.clone(), v);
+        }
+        let mut post = callee.ensures.substitute(&map);
+        if let Some(gas) = state.gas().cloned() {
+            pre = pre.and(Self::sufficient(callee, &gas, &map));
+            let g = Term::var(&format!("%gas.{}", self.fresh), gas.width());
+            state.set_gas(g.clone());
+            post = post.and(gas.less_than(g).nonzero().not());
+        }
+        Some((pre, post))
+    }
+
+    /// Determine the condition under which given gas meets the bound
+    /// of a given summary, where its arguments are replaced according
+    /// to a given map.
+    fn sufficient(summary: &Summary, gas: &Term, map: &HashMap<Term,Term>) -> Term {
+        match &summary.gas {
+            Some(bound) => gas.clone().less_than(bound.substitute(map)).nonzero().not(),
+            None => Term::bool(true)
+        }
+    }
+
+    /// Determine the condition under which a state returning from a
+    /// given subroutine establishes its postcondition, which requires
+    /// exactly its results to be on the stack.
+    fn ret(summary: &Summary, state: &M::State) -> Term {
+        let n = summary.results.len();
+        let items : Option<Vec<Term>> = (0..n).rev().map(|i| state.peek(i).ok().cloned()).collect();
+        match items {
+            Some(items) if state.size() == n => {
+                let map : HashMap<Term,Term> = summary.results.iter().cloned().zip(items).collect();
+                summary.ensures.substitute(&map)
+            }
+            _ => Term::bool(false)
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash,Hasher};
+use std::rc::{Rc,Weak};
+use crate::{BitVec,MachineWord};
+
+// ===================================================================
+// Term
+// ===================================================================
+
+/// A symbolic word, represented as an expression tree over variables
+/// and constants.  Terms are _hash-consed_: structurally identical
+/// terms are always represented by the same node and, hence,
+/// equality between terms is a constant-time pointer comparison.
+/// Furthermore, terms are simplified on construction (e.g. constant
+/// folding), so that running a machine over a `VecState<Term>` yields
+/// a (reasonably) compact symbolic value for each stack slot.
+///
+/// As for `BitVec`, every term has a fixed width and binary
+/// operations require both operands to have the same width.
+/// Comparisons produce either `0` or `1` (at the width of their
+/// operands), and division (or remainder) by zero produces zero.
+#[derive(Clone)]
+pub struct Term(Rc<Node>);
+
+struct Node {
+    /// Unique identifier for this node.
+    id: usize,
+    /// Width (in bits) of this term.
+    width: usize,
+    /// Structure of this term.
+    kind: TermKind
+}
+
+/// Describes the structure of a given term.
+#[derive(Clone,Debug,PartialEq,Eq,Hash)]
+pub enum TermKind {
+    /// A free variable.
+    Var(String),
+    /// A constant value.
+    Const(BitVec),
+    /// A unary operation applied to an operand.
+    Unary(UnaryOp,Term),
+    /// A binary operation applied to two operands.
+    Binary(BinaryOp,Term,Term),
+    /// A conditional which produces its second operand if the (1bit)
+    /// condition holds, and its third otherwise.
+    Ite(Term,Term,Term)
+}
+
+/// Unary operations over terms.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    /// Produces a 1bit term which is `1` if its operand is non-zero,
+    /// and `0` otherwise.
+    NonZero
+}
+
+/// Binary operations over terms.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum BinaryOp {
+    // Comparators
+    LessThan,
+    Equal,
+    // Arithmetic
+    Add,
+    Mul,
+    Div,
+    Rem,
+    // Bitwise
+    And,
+    Or,
+    Xor
+}
+
+impl BinaryOp {
+    /// Check whether this operation is commutative.
+    pub fn is_commutative(&self) -> bool {
+        matches!(self, BinaryOp::Equal|BinaryOp::Add|BinaryOp::Mul
+                 |BinaryOp::And|BinaryOp::Or|BinaryOp::Xor)
+    }
+}
+
+impl Term {
+    /// Construct a free variable of a given width.
+    pub fn var(name: &str, width: usize) -> Self {
+        assert!(width > 0, "term width must be non-zero");
+        Self::intern(TermKind::Var(name.to_string()), width)
+    }
+
+    /// Construct a constant term.
+    pub fn constant(value: BitVec) -> Self {
+        let width = value.width();
+        Self::intern(TermKind::Const(value), width)
+    }
+
+    /// Construct a constant term of a given width from a `u64`
+    /// value.
+    pub fn from_u64(width: usize, value: u64) -> Self {
+        Self::constant(BitVec::from_u64(width, value))
+    }
+
+    /// Construct a 1bit constant term representing a boolean.
+    pub fn bool(value: bool) -> Self {
+        Self::from_u64(1, value as u64)
+    }
+
+    /// Get the unique identifier of this term.  Since terms are
+    /// hash-consed, two terms have the same identifier if and only
+    /// if they are structurally identical.
+    pub fn id(&self) -> usize { self.0.id }
+
+    /// Get the width (in bits) of this term.
+    pub fn width(&self) -> usize { self.0.width }
+
+    /// Get the structure of this term.
+    pub fn kind(&self) -> &TermKind { &self.0.kind }
+
+    /// Get the value of this term, if it is a constant.
+    pub fn as_constant(&self) -> Option<&BitVec> {
+        match self.kind() {
+            TermKind::Const(v) => Some(v),
+            _ => None
+        }
+    }
+
+    /// Construct a 1bit term which holds when this term is non-zero.
+    /// Since machine words represent booleans as `0` or `1`, this
+    /// converts words into conditions.
+    pub fn nonzero(self) -> Term {
+        Term::unary(UnaryOp::NonZero, self)
+    }
+
+    /// Construct a 1bit term representing the implication `self ==>
+    /// rhs`, where both are 1bit terms.
+    pub fn implies(self, rhs: Term) -> Term {
+        self.not().or(rhs)
+    }
+
+    /// Construct (and simplify) a conditional term, which produces
+    /// `lhs` when the (1bit) condition holds, and `rhs` otherwise.
+    pub fn ite(cond: Term, lhs: Term, rhs: Term) -> Term {
+        assert_eq!(cond.width(), 1, "condition must be 1bit");
+        assert_eq!(lhs.width(), rhs.width(), "term width mismatch");
+        let width = lhs.width();
+        if let Some(c) = cond.as_constant() {
+            if c.is_zero() { rhs } else { lhs }
+        } else if lhs == rhs {
+            lhs
+        } else if width == 1 && lhs == Term::bool(true) && rhs == Term::bool(false) {
+            cond
+        } else if width == 1 && lhs == Term::bool(false) && rhs == Term::bool(true) {
+            cond.not()
+        } else {
+            Self::intern(TermKind::Ite(cond,lhs,rhs), width)
+        }
+    }
+
+    /// Replace each occurrence of a given term (e.g. a variable) with
+    /// its corresponding term in a given mapping, simplifying the
+    /// result.
+    pub fn substitute(&self, map: &HashMap<Term,Term>) -> Term {
+        self.substitute_with(map, &mut HashMap::new())
+    }
+
+    fn substitute_with(&self, map: &HashMap<Term,Term>, cache: &mut HashMap<Term,Term>) -> Term {
+        if let Some(t) = map.get(self).or_else(|| cache.get(self)) {
+            return t.clone();
+        }
+        let t = match self.kind() {
+            TermKind::Var(_)|TermKind::Const(_) => self.clone(),
+            TermKind::Unary(op,t) => Term::unary(*op, t.substitute_with(map, cache)),
+            TermKind::Binary(op,l,r) => {
+                Term::binary(*op, l.substitute_with(map, cache), r.substitute_with(map, cache))
+            }
+            TermKind::Ite(c,l,r) => {
+                let c = c.substitute_with(map, cache);
+                Term::ite(c, l.substitute_with(map, cache), r.substitute_with(map, cache))
+            }
+        };
+        cache.insert(self.clone(), t.clone());
+        t
+    }
+
+    /// Construct (and simplify) a unary operation.
+    pub fn unary(op: UnaryOp, arg: Term) -> Term {
+        // Constant folding
+        if let Some(v) = arg.as_constant() {
+            let v = v.clone();
+            return match op {
+                UnaryOp::Neg => Term::constant(v.neg()),
+                UnaryOp::Not => Term::constant(v.not()),
+                UnaryOp::NonZero => Term::bool(!v.is_zero())
+            };
+        }
+        match (op,arg.kind()) {
+            // Conditions are already 1bit
+            (UnaryOp::NonZero,_) if arg.width() == 1 => {
+                return arg;
+            }
+            (UnaryOp::NonZero,TermKind::Binary(BinaryOp::Equal,l,r))
+                if r.as_constant().is_some_and(|v| v.is_zero()) => {
+                return l.clone().nonzero().not();
+            }
+            (UnaryOp::NonZero,TermKind::Ite(c,l,r))
+                if l.as_constant().is_some() && r.as_constant().is_some() => {
+                return Term::ite(c.clone(), l.clone().nonzero(), r.clone().nonzero());
+            }
+            (UnaryOp::NonZero,_) => {
+                return Self::intern(TermKind::Unary(op,arg), 1);
+            }
+            // Eliminate double negation
+            (_,TermKind::Unary(op2,t)) if *op2 == op => {
+                return t.clone();
+            }
+            _ => {}
+        }
+        let width = arg.width();
+        Self::intern(TermKind::Unary(op,arg), width)
+    }
+
+    /// Evaluate this term under a given assignment of values to
+    /// variables.  If any variable is unassigned, then `None` is
+    /// returned.
+    pub fn eval<F>(&self, env: &F) -> Option<BitVec>
+    where F: Fn(&str)->Option<BitVec> {
+        let v = match self.kind() {
+            TermKind::Var(n) => env(n)?,
+            TermKind::Const(v) => v.clone(),
+            TermKind::Unary(op,t) => {
+                let v = t.eval(env)?;
+                match op {
+                    UnaryOp::Neg => v.neg(),
+                    UnaryOp::Not => v.not(),
+                    UnaryOp::NonZero => BitVec::from_u64(1, !v.is_zero() as u64)
+                }
+            }
+            TermKind::Binary(op,l,r) => Self::fold(*op, l.eval(env)?, r.eval(env)?),
+            TermKind::Ite(c,l,r) => {
+                if c.eval(env)?.is_zero() { r.eval(env)? } else { l.eval(env)? }
+            }
+        };
+        assert_eq!(v.width(), self.width(), "variable width mismatch");
+        Some(v)
+    }
+
+    /// Construct (and simplify) a binary operation.
+    pub fn binary(op: BinaryOp, lhs: Term, rhs: Term) -> Term {
+        assert_eq!(lhs.width(), rhs.width(), "term width mismatch");
+        let width = lhs.width();
+        // Normalise operands of commutative operations, such that
+        // constants are on the right.
+        let (lhs,rhs) = if op.is_commutative() && Self::order(&lhs,&rhs) {
+            (rhs,lhs)
+        } else {
+            (lhs,rhs)
+        };
+        match (lhs.as_constant(),rhs.as_constant()) {
+            (Some(l),Some(r)) => {
+                Term::constant(Self::fold(op, l.clone(), r.clone()))
+            }
+            (_,Some(r)) => {
+                Self::simplify_const(op, lhs.clone(), r)
+                    .unwrap_or_else(|| Self::intern(TermKind::Binary(op,lhs,rhs),width))
+            }
+            (Some(l),None) => {
+                Self::simplify_lhs_const(op, l, &rhs)
+                    .unwrap_or_else(|| Self::intern(TermKind::Binary(op,lhs,rhs),width))
+            }
+            (None,None) => {
+                Self::simplify_same(op, &lhs, &rhs)
+                    .unwrap_or_else(|| Self::intern(TermKind::Binary(op,lhs,rhs),width))
+            }
+        }
+    }
+
+    /// Determine whether the operands of a commutative operation
+    /// should be swapped.
+    fn order(lhs: &Term, rhs: &Term) -> bool {
+        match (lhs.as_constant(),rhs.as_constant()) {
+            (Some(_),None) => true,
+            (None,Some(_)) => false,
+            _ => lhs.id() > rhs.id()
+        }
+    }
+
+    /// Evaluate a binary operation over constant operands.
+    fn fold(op: BinaryOp, l: BitVec, r: BitVec) -> BitVec {
+        match op {
+            BinaryOp::LessThan => l.less_than(r),
+            BinaryOp::Equal => l.equal(r),
+            BinaryOp::Add => l.add(r),
+            BinaryOp::Mul => l.mul(r),
+            BinaryOp::Div => l.div(r),
+            BinaryOp::Rem => l.rem(r),
+            BinaryOp::And => l.and(r),
+            BinaryOp::Or => l.or(r),
+            BinaryOp::Xor => l.xor(r)
+        }
+    }
+
+    /// Simplify a binary operation whose right-hand side is constant.
+    fn simplify_const(op: BinaryOp, lhs: Term, r: &BitVec) -> Option<Term> {
+        let w = lhs.width();
+        let one = BitVec::from_u64(w, 1);
+        match op {
+            BinaryOp::LessThan if r.is_zero() => Some(Term::from_u64(w,0)),
+            BinaryOp::Add|BinaryOp::Or|BinaryOp::Xor if r.is_zero() => Some(lhs),
+            BinaryOp::Mul|BinaryOp::And if r.is_zero() => Some(Term::from_u64(w,0)),
+            BinaryOp::Div|BinaryOp::Rem if r.is_zero() => Some(Term::from_u64(w,0)),
+            BinaryOp::Mul|BinaryOp::Div if *r == one => Some(lhs),
+            BinaryOp::Rem if *r == one => Some(Term::from_u64(w,0)),
+            BinaryOp::And if *r == BitVec::ones(w) => Some(lhs),
+            BinaryOp::Or if *r == BitVec::ones(w) => Some(Term::constant(r.clone())),
+            BinaryOp::Xor if *r == BitVec::ones(w) => Some(lhs.not()),
+            // Reassociate constant offsets, i.e. (t+c1)+c2 ==> t+(c1+c2)
+            BinaryOp::Add => match lhs.kind() {
+                TermKind::Binary(BinaryOp::Add,t,c) => {
+                    let c = c.as_constant()?.clone().add(r.clone());
+                    Some(Term::binary(BinaryOp::Add, t.clone(), Term::constant(c)))
+                }
+                _ => None
+            },
+            _ => None
+        }
+    }
+
+    /// Simplify a (non-commutative) binary operation whose left-hand
+    /// side is constant.
+    fn simplify_lhs_const(op: BinaryOp, l: &BitVec, rhs: &Term) -> Option<Term> {
+        let w = rhs.width();
+        match op {
+            BinaryOp::LessThan if *l == BitVec::ones(w) => Some(Term::from_u64(w,0)),
+            BinaryOp::Div|BinaryOp::Rem if l.is_zero() => Some(Term::from_u64(w,0)),
+            _ => None
+        }
+    }
+
+    /// Simplify a binary operation whose operands are identical (or
+    /// complementary).
+    fn simplify_same(op: BinaryOp, lhs: &Term, rhs: &Term) -> Option<Term> {
+        let w = lhs.width();
+        if lhs.is_complement(rhs) || rhs.is_complement(lhs) {
+            return match op {
+                BinaryOp::And => Some(Term::from_u64(w,0)),
+                BinaryOp::Or|BinaryOp::Xor => Some(Term::constant(BitVec::ones(w))),
+                _ => None
+            };
+        } else if lhs != rhs {
+            return None;
+        }
+        match op {
+            BinaryOp::LessThan|BinaryOp::Xor => Some(Term::from_u64(w,0)),
+            BinaryOp::Equal => Some(Term::from_u64(w,1)),
+            BinaryOp::And|BinaryOp::Or => Some(lhs.clone()),
+            _ => None
+        }
+    }
+
+    /// Check whether this term is the bitwise complement of another.
+    fn is_complement(&self, other: &Term) -> bool {
+        matches!(self.kind(), TermKind::Unary(UnaryOp::Not,t) if t == other)
+    }
+
+    /// Construct the unique node for a given term structure.
+    fn intern(kind: TermKind, width: usize) -> Term {
+        TABLE.with(|t| t.borrow_mut().intern(kind, width))
+    }
+}
+
+/// Terms are equal when they are structurally identical which, due
+/// to hash-consing, is determined by their identifiers.
+impl PartialEq for Term {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Term {}
+
+impl Hash for Term {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
+}
+
+impl fmt::Debug for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind() {
+            TermKind::Var(n) => write!(f, "{}", n),
+            TermKind::Const(v) => write!(f, "{}", v),
+            TermKind::Unary(UnaryOp::Neg,t) => write!(f, "-{}", t),
+            TermKind::Unary(UnaryOp::Not,t) => write!(f, "!{}", t),
+            TermKind::Unary(UnaryOp::NonZero,t) => write!(f, "nz({})", t),
+            TermKind::Ite(c,l,r) => write!(f, "({} ? {} : {})", c, l, r),
+            TermKind::Binary(op,l,r) => {
+                let s = match op {
+                    BinaryOp::LessThan => "<",
+                    BinaryOp::Equal => "==",
+                    BinaryOp::Add => "+",
+                    BinaryOp::Mul => "*",
+                    BinaryOp::Div => "/",
+                    BinaryOp::Rem => "%",
+                    BinaryOp::And => "&",
+                    BinaryOp::Or => "|",
+                    BinaryOp::Xor => "^"
+                };
+                write!(f, "({} {} {})", l, s, r)
+            }
+        }
+    }
+}
+
+impl MachineWord for Term {
+    // Comparators
+    fn less_than(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::LessThan,self,rhs)
+    }
+    fn equal(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Equal,self,rhs)
+    }
+    // Arithmetic
+    fn add(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Add,self,rhs)
+    }
+    fn sub(self,rhs:Self)->Self {
+        // Subtraction is represented using negation, which keeps the
+        // set of operators small.
+        self.add(rhs.neg())
+    }
+    fn mul(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Mul,self,rhs)
+    }
+    fn div(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Div,self,rhs)
+    }
+    fn rem(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Rem,self,rhs)
+    }
+    fn neg(self)->Self {
+        Term::unary(UnaryOp::Neg,self)
+    }
+    // Bitwise
+    fn and(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::And,self,rhs)
+    }
+    fn or(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Or,self,rhs)
+    }
+    fn xor(self,rhs:Self)->Self {
+        Term::binary(BinaryOp::Xor,self,rhs)
+    }
+    fn not(self)->Self {
+        Term::unary(UnaryOp::Not,self)
+    }
+    // Truthiness
+    fn to_bool(&self)->Option<bool> {
+        self.as_constant().map(|v| !v.is_zero())
+    }
+    // Conversions
+    fn constant_like(&self, value: u64)->Self {
+        Term::from_u64(self.width(), value)
+    }
+    fn to_usize(&self)->Option<usize> {
+        self.as_constant().and_then(|v| v.to_usize())
+    }
+    fn to_bitvec(&self)->Option<BitVec> {
+        self.as_constant().cloned()
+    }
+}
+
+// ===================================================================
+// Hash-Consing
+// ===================================================================
+
+thread_local! {
+    static TABLE: RefCell<TermTable> = RefCell::new(TermTable::new());
+}
+
+/// Minimum number of entries in the term table before it is purged
+/// of dead entries.
+const MIN_PURGE_THRESHOLD : usize = 1024;
+
+/// Maps each term structure (and width, since variables of the same
+/// name may differ in width) to the unique node representing it.
+/// Nodes are held weakly, so that unused terms can be reclaimed.
+struct TermTable {
+    nodes: HashMap<(usize,TermKind),Weak<Node>>,
+    next_id: usize,
+    threshold: usize
+}
+
+impl TermTable {
+    fn new() -> Self {
+        Self{nodes: HashMap::new(), next_id: 0, threshold: MIN_PURGE_THRESHOLD}
+    }
+
+    fn intern(&mut self, kind: TermKind, width: usize) -> Term {
+        let key = (width,kind);
+        if let Some(n) = self.nodes.get(&key).and_then(|w| w.upgrade()) {
+            return Term(n);
+        }
+        if self.nodes.len() >= self.threshold {
+            self.purge();
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let node = Rc::new(Node{id, width, kind: key.1.clone()});
+        self.nodes.insert(key, Rc::downgrade(&node));
+        Term(node)
+    }
+
+    /// Remove all entries whose nodes have been reclaimed.
+    fn purge(&mut self) {
+        self.nodes.retain(|_,w| w.strong_count() > 0);
+        self.threshold = MIN_PURGE_THRESHOLD.max(2 * self.nodes.len());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::{MachineState,MachineWord,StepMachine,StepOutcome,Term};
+
+// ===================================================================
+// Verifiable Machines
+// ===================================================================
+
+/// A single-step machine which can be verified over symbolic states.
+/// Specifically, such a machine identifies the _obligations_ which
+/// must hold for an instruction to execute correctly (e.g. the
+/// condition of an assertion), along with the _guards_ under which
+/// execution proceeds to each state of a fork.  Obligations and
+/// guards are machine words which hold when they are non-zero.
+pub trait Verifiable : StepMachine {
+    /// Determine the condition (if any) which must hold for the
+    /// instruction at the current position of a given state to
+    /// execute correctly.
+    fn obligation(&self, _state: &Self::State) -> Option<Term> {
+        None
+    }
+
+    /// Determine whether the instruction at the current position of a
+    /// given state may access more than its stack and call stack (e.g.
+    /// by reading or writing its memory or storage).
+    fn accesses(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    /// Determine the condition under which execution proceeds from a
+    /// given state to a given successor, where executing the former
+    /// forked.
+    fn guard(&self, state: &Self::State, successor: &Self::State) -> Term;
+}
+
+/// A symbolic state which can be merged with another at a
+/// control-flow join point.
+pub trait Merge : Sized {
+    /// Merge two states at the same position, such that the result
+    /// corresponds with `lhs` when a given (1bit) condition holds,
+    /// and `rhs` otherwise.  If the states cannot be merged (e.g.
+    /// because their stack sizes differ) then `None` is returned.
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self>;
+}
+
+// ===================================================================
+// VC Generation
+// ===================================================================
+
+/// Errors which prevent a verification condition from being
+/// generated.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum VcError {
+    /// Control-flow was encountered which does not proceed strictly
+    /// forwards and, hence, the program may not be acyclic.
+    BackwardsJump{from: usize, to: usize},
+    /// A call to a summarised subroutine was encountered whose number
+    /// of arguments does not match its summary.
+    SummaryMismatch{pc: usize},
+    /// An instruction was encountered in the body of a summarised
+    /// subroutine which accesses more than its stack (e.g. memory or
+    /// storage) and, hence, is not described by its summary.
+    SummaryEffect{pc: usize}
+}
+
+impl fmt::Display for VcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for VcError {
+
+}
+
+/// Generate a single verification condition for an acyclic program,
+/// starting from a given (symbolic) initial state.  The condition is
+/// a 1bit term whose validity implies that, for every execution from
+/// the initial state, no obligation fails and no error is raised.
+///
+/// The condition is the weakest precondition of the program which,
+/// for acyclic programs, is computed in a single forwards pass.
+/// States reaching the same position are merged (where possible) and,
+/// hence, paths are not enumerated one by one.  Since control-flow
+/// must proceed strictly forwards, an error is returned for any
+/// backwards jump.
+pub fn generate_vc<M>(machine: &M, init: M::State) -> Result<Term,VcError>
+where M: Verifiable, M::State: MachineState<Word=Term>+Merge+Clone {
+    let mut pending : BTreeMap<usize,Vec<(Term,M::State)>> = BTreeMap::new();
+    let mut vc = Term::bool(true);
+    pending.insert(init.pc(), vec![(Term::bool(true),init)]);
+    //
+    while let Some((pc,states)) = pending.pop_first() {
+        for (guard,state) in merge_all(states) {
+            for (g,s) in successors(machine, guard, state, &mut vc) {
+                if s.pc() <= pc {
+                    return Err(VcError::BackwardsJump{from: pc, to: s.pc()});
+                }
+                pending.entry(s.pc()).or_default().push((g,s));
+            }
+        }
+    }
+    Ok(vc)
+}
+
+/// Execute a given state (reached under a given guard), returning its
+/// successors (and their guards) whilst adding the obligations of the
+/// instruction executed to a given verification condition.
+pub(crate) fn successors<M:Verifiable>(machine: &M, guard: Term, state: M::State, vc: &mut Term) -> Vec<(Term,M::State)>
+where M::State: Clone {
+    // Check instruction executes correctly
+    if let Some(ob) = machine.obligation(&state) {
+        *vc = vc.clone().and(guard.clone().implies(ob.nonzero()));
+    }
+    match machine.execute(state.clone()) {
+        Ok(StepOutcome::Continue(s)) => vec![(guard,s)],
+        Ok(StepOutcome::Fork(ss)) => {
+            ss.into_iter().map(|s| {
+                let g = machine.guard(&state,&s).nonzero();
+                (guard.clone().and(g),s)
+            }).collect()
+        }
+        Ok(_) => vec![],
+        Err(_) => {
+            // Errors must be unreachable
+            *vc = vc.clone().and(guard.not());
+            vec![]
+        }
+    }
+}
+
+/// Merge as many states (at the same position) as possible.
+pub(crate) fn merge_all<S:Merge>(states: Vec<(Term,S)>) -> Vec<(Term,S)> {
+    let mut merged : Vec<(Term,S)> = Vec::new();
+    'outer: for (g,s) in states {
+        for (mg,ms) in merged.iter_mut() {
+            if let Some(m) = S::merge(&g, &s, ms) {
+                *mg = g.or(mg.clone());
+                *ms = m;
+                continue 'outer;
+            }
+        }
+        merged.push((g,s));
+    }
+    merged
+}
@@ -1,60 +1,306 @@
 use std::marker::PhantomData;
-use crate::{Machine,MachineError,MachineState,MachineWord,MinimalMachineError};
+use crate::{CallState,ConcreteStorage,DEFAULT_MAX_CALLS,Frame,GasState,Lattice,MachineError,MachineState,MachineWord,Memory,MemoryState,Merge,MinimalMachineError};
+use crate::{Storage,StorageState,Term,WordMemory};
+
+/// The default maximum depth of a `VecState` stack (as for the EVM).
+pub const DEFAULT_MAX_DEPTH : usize = 1024;
 
 /// A minimal implementation of `MachineState` which represents data
 /// using a single, flat, vector.  As such, this is primarily useful
-/// for runtime execution rather than abstract execution.
-pub struct VecState<T,E=MinimalMachineError> {
+/// for runtime execution rather than abstract execution.  Each state
+/// additionally has a linear memory and persistent storage which, by
+/// default, require their addresses (and keys) to be known.  Each
+/// state also has a call stack and (optionally) a gas counter.
+#[derive(Clone,Debug,PartialEq)]
+pub struct VecState<T,E=MinimalMachineError,M=WordMemory<T,E>,S=ConcreteStorage<T,E>> {
     dummy: PhantomData<E>,
     pc: usize,
-    stack: Vec<T>
+    stack: Vec<T>,
+    /// Maximum number of items which can be held on the stack.
+    max_depth: usize,
+    memory: M,
+    storage: S,
+    /// Gas remaining, or `None` if execution is not metered.
+    gas: Option<T>,
+    frames: Vec<Frame<T>>,
+    /// Maximum number of frames which can be held on the call stack.
+    max_calls: usize
 }
 
 impl<T,E> VecState<T,E> {
     pub fn init() -> Self {
-        Self{pc:0, stack: Vec::new(), dummy: PhantomData}
+        Self{pc:0, stack: Vec::new(), max_depth: DEFAULT_MAX_DEPTH, memory: WordMemory::new(), storage: ConcreteStorage::new(), gas: None,
+             frames: Vec::new(), max_calls: DEFAULT_MAX_CALLS, dummy: PhantomData}
+    }
+
+    /// Set the maximum size (in bytes) of the memory, beyond which
+    /// accessing memory fails.
+    pub fn max_memory(mut self, n: usize) -> Self {
+        self.memory = self.memory.max_size(n);
+        self
+    }
+
+    /// Construct a new state by applying a given function to each
+    /// item on the stack (and in memory, storage, gas or frames).  For
+    /// example, this can be used to instantiate a symbolic state with
+    /// the values of a model.
+    pub fn map<U,F:Fn(&T)->U>(&self, f: F) -> VecState<U,E> {
+        let stack = self.stack.iter().map(&f).collect();
+        let memory = self.memory.map(&f);
+        let storage = self.storage.map(&f);
+        let gas = self.gas.as_ref().map(&f);
+        let frames = self.frames.iter().map(|fr| fr.map(&f)).collect();
+        VecState{pc: self.pc, stack, max_depth: self.max_depth, memory, storage, gas, frames,
+                 max_calls: self.max_calls, dummy: PhantomData}
     }
 }
 
-impl<T:MachineWord,E:MachineError> MachineState for VecState<T,E> {
+impl<T,E,M,S> VecState<T,E,M,S> {
+    /// Set the maximum number of items which can be held on the
+    /// stack, beyond which pushing an item fails.
+    pub fn max_depth(mut self, n: usize) -> Self {
+        self.max_depth = n;
+        self
+    }
+
+    /// Set the maximum number of frames which can be held on the call
+    /// stack, beyond which calling a subroutine fails.
+    pub fn max_calls(mut self, n: usize) -> Self {
+        self.max_calls = n;
+        self
+    }
+
+    /// Set the gas available, such that execution is metered.
+    pub fn gas(mut self, gas: T) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    /// Replace the memory of this state with a given memory (which
+    /// may be of a different kind).
+    pub fn with_memory<N>(self, memory: N) -> VecState<T,E,N,S> {
+        VecState{pc: self.pc, stack: self.stack, max_depth: self.max_depth, memory, storage: self.storage, gas: self.gas,
+                 frames: self.frames, max_calls: self.max_calls, dummy: PhantomData}
+    }
+
+    /// Replace the storage of this state with a given storage (which
+    /// may be of a different kind).
+    pub fn with_storage<N>(self, storage: N) -> VecState<T,E,M,N> {
+        VecState{pc: self.pc, stack: self.stack, max_depth: self.max_depth, memory: self.memory, storage, gas: self.gas,
+                 frames: self.frames, max_calls: self.max_calls, dummy: PhantomData}
+    }
+}
+
+impl<T:MachineWord,E:MachineError,M,S> MachineState for VecState<T,E,M,S> {
     type Word = T;
     type Error = E;
-    
+
     fn size(&self) -> usize { self.stack.len() }
 
     fn pc(&self) -> usize { self.pc }
-    
+
     fn peek(&self, n: usize) -> Result<&Self::Word,Self::Error> {
-        if self.stack.len() > n {
-            let m = self.stack.len() - (n+1);
-            Ok(&self.stack[m])
-        } else {
-            Err(E::stack_underflow())
-        }
+        let m = self.index(n)?;
+        Ok(&self.stack[m])
     }
 
     fn push(&mut self, item: Self::Word) -> Result<(),Self::Error> {
-        Ok(self.stack.push(item))
+        if self.stack.len() >= self.max_depth {
+            return Err(E::stack_overflow());
+        }
+        self.stack.push(item);
+        Ok(())
     }
 
     fn pop(&mut self) -> Result<Self::Word,Self::Error> {
-        if self.stack.len() > 0 {
-            Ok(self.stack.pop().unwrap())
-        } else {
-            Err(E::stack_underflow())            
-        }
+        self.stack.pop().ok_or_else(E::stack_underflow)
     }
 
     fn set(&mut self, n: usize, item: Self::Word) -> Result<Self::Word,Self::Error> {
-        todo!()
+        let m = self.index(n)?;
+        Ok(std::mem::replace(&mut self.stack[m], item))
     }
 
     fn swap(&mut self, n: usize) -> Result<(),Self::Error> {
-        todo!()
+        let m = self.index(n)?;
+        let top = self.stack.len() - 1;
+        self.stack.swap(m, top);
+        Ok(())
     }
 
     /// Set position within instruction sequence.
     fn goto(&mut self, pc: usize) {
         self.pc = pc;
-    }    
+    }
+}
+
+impl<T:MachineWord,E:MachineError,M,S> MemoryState for VecState<T,E,M,S>
+where M: Memory<Word=T,Error=E> {
+    type Memory = M;
+
+    fn memory(&self) -> &Self::Memory { &self.memory }
+
+    fn memory_mut(&mut self) -> &mut Self::Memory { &mut self.memory }
+}
+
+impl<T:MachineWord,E:MachineError,M,S> StorageState for VecState<T,E,M,S>
+where S: Storage<Word=T,Error=E> {
+    type Storage = S;
+
+    fn storage(&self) -> &Self::Storage { &self.storage }
+
+    fn storage_mut(&mut self) -> &mut Self::Storage { &mut self.storage }
+}
+
+impl<T:MachineWord,E:MachineError,M,S> GasState for VecState<T,E,M,S> {
+    fn gas(&self) -> Option<&T> { self.gas.as_ref() }
+
+    fn set_gas(&mut self, gas: T) { self.gas = Some(gas); }
+}
+
+/// Each frame on the call stack has a corresponding checkpoint of the
+/// storage (i.e. the `nth` frame has the `nth` checkpoint), which is
+/// recorded when the frame is pushed and discarded (keeping all
+/// stores made since) when it is popped.
+impl<T:MachineWord,E:MachineError,M,S> CallState for VecState<T,E,M,S>
+where S: Storage<Word=T,Error=E> {
+    fn depth(&self) -> usize { self.frames.len() }
+
+    fn frame(&self) -> Option<&Frame<T>> { self.frames.last() }
+
+    fn frame_mut(&mut self) -> Option<&mut Frame<T>> { self.frames.last_mut() }
+
+    fn call(&mut self, frame: Frame<T>) -> Result<(),E> {
+        if self.frames.len() >= self.max_calls {
+            return Err(E::call_depth_exceeded());
+        }
+        self.storage.checkpoint();
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    fn ret(&mut self) -> Result<Frame<T>,E> {
+        let frame = self.frames.pop().ok_or_else(E::invalid_return)?;
+        self.storage.commit(self.frames.len());
+        Ok(frame)
+    }
+}
+
+/// States are ordered pointwise over their stacks (and memories,
+/// storages, gas and local slots).  Since the size of the stack is
+/// always known, states being compared (or merged) must have stacks of
+/// the same size, be at the same position within the instruction
+/// sequence, have the same call stack shape, and either both be
+/// metered or not.
+impl<T:Lattice,E,M:Lattice,S:Lattice> Lattice for VecState<T,E,M,S> {
+    fn bottom(&self) -> Self {
+        self.zip(self, self.memory.bottom(), self.storage.bottom(), |w,_| w.bottom())
+    }
+
+    fn top(&self) -> Self {
+        self.zip(self, self.memory.top(), self.storage.top(), |w,_| w.top())
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let (m,s) = (self.memory.join(&other.memory),self.storage.join(&other.storage));
+        self.zip(other, m, s, |l,r| l.join(r))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let (m,s) = (self.memory.meet(&other.memory),self.storage.meet(&other.storage));
+        self.zip(other, m, s, |l,r| l.meet(r))
+    }
+
+    fn compatible(&self, other: &Self) -> bool {
+        self.stack.len() == other.stack.len()
+            && self.pc == other.pc
+            && self.gas.is_some() == other.gas.is_some()
+            && self.same_frames(other)
+            && self.memory.compatible(&other.memory)
+            && self.storage.compatible(&other.storage)
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.check_shape(other);
+        self.stack.iter().zip(other.stack.iter()).all(|(l,r)| l.leq(r))
+            && self.memory.leq(&other.memory)
+            && self.storage.leq(&other.storage)
+            && match (&self.gas,&other.gas) {
+                (Some(l),Some(r)) => l.leq(r),
+                _ => true
+            }
+            && self.frames.iter().zip(other.frames.iter()).all(|(l,r)| {
+                l.locals().iter().zip(r.locals().iter()).all(|(l,r)| l.leq(r))
+            })
+    }
+
+    fn widen(&self, other: &Self) -> Self {
+        let (m,s) = (self.memory.widen(&other.memory),self.storage.widen(&other.storage));
+        self.zip(other, m, s, |l,r| l.widen(r))
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        let (m,s) = (self.memory.narrow(&other.memory),self.storage.narrow(&other.storage));
+        self.zip(other, m, s, |l,r| l.narrow(r))
+    }
+}
+
+/// Symbolic states are merged pointwise over their stacks (and
+/// memories, storages, gas and local slots), provided their stacks
+/// have the same size, their call stacks have the same shape, and they
+/// are either both metered or not.
+impl<E,M:Merge,S:Merge> Merge for VecState<Term,E,M,S> {
+    fn merge(cond: &Term, lhs: &Self, rhs: &Self) -> Option<Self> {
+        if lhs.stack.len() == rhs.stack.len() && lhs.gas.is_some() == rhs.gas.is_some()
+            && lhs.same_frames(rhs) {
+            let memory = M::merge(cond, &lhs.memory, &rhs.memory)?;
+            let storage = S::merge(cond, &lhs.storage, &rhs.storage)?;
+            Some(lhs.zip(rhs, memory, storage, |l,r| Term::ite(cond.clone(), l.clone(), r.clone())))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T,E,M,S> VecState<T,E,M,S> {
+    /// Determine the index of the `nth` item from the top of the
+    /// stack, or fail if there is no such item.
+    fn index(&self, n: usize) -> Result<usize,E> where E: MachineError {
+        if self.stack.len() > n {
+            Ok(self.stack.len() - (n+1))
+        } else {
+            Err(E::stack_underflow())
+        }
+    }
+
+    fn check_shape(&self, other: &Self) {
+        assert_eq!(self.stack.len(), other.stack.len(), "stack size mismatch");
+        assert_eq!(self.pc, other.pc, "pc mismatch");
+        assert_eq!(self.gas.is_some(), other.gas.is_some(), "gas mismatch");
+        assert!(self.same_frames(other), "call stack mismatch");
+    }
+
+    /// Check two states have call stacks of the same shape (i.e. the
+    /// same return positions and numbers of local slots).
+    fn same_frames(&self, other: &Self) -> bool {
+        self.frames.len() == other.frames.len()
+            && self.frames.iter().zip(other.frames.iter()).all(|(l,r)| {
+                l.return_pc() == r.return_pc() && l.locals().len() == r.locals().len()
+            })
+    }
+
+    /// Combine the stacks of two states pointwise, along with an
+    /// already combined memory and storage.  Gas and local slots are
+    /// combined as for the stack.
+    fn zip<F:Fn(&T,&T)->T>(&self, other: &Self, memory: M, storage: S, f: F) -> Self {
+        self.check_shape(other);
+        let stack = self.stack.iter().zip(other.stack.iter()).map(|(l,r)| f(l,r)).collect();
+        let gas = self.gas.as_ref().zip(other.gas.as_ref()).map(|(l,r)| f(l,r));
+        let frames = self.frames.iter().zip(other.frames.iter()).map(|(l,r)| {
+            let locals = l.locals().iter().zip(r.locals().iter()).map(|(l,r)| f(l,r)).collect();
+            Frame::new(l.return_pc(), locals)
+        }).collect();
+        Self{pc: self.pc, stack, max_depth: self.max_depth, memory, storage, gas, frames,
+             max_calls: self.max_calls, dummy: PhantomData}
+    }
 }
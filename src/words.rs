@@ -1,39 +1,80 @@
-use crate::{MachineWord};
+use crate::{BitVec,MachineWord};
 
-impl MachineWord for u8 {
-    fn less_than(self,rhs:Self)->Self {
-        if self < rhs { 1 } else { 0 }
-    }
-    fn equal(self,rhs:Self)->Self {
-        if self == rhs { 1 } else { 0 }
-    }
-    // Arithmetic
-    fn add(self,rhs:Self)->Self {
-        self.wrapping_add(rhs)
-    }
-    fn mul(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn div(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn rem(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn neg(self)->Self {
-        todo!();
-    }
-    // Bitwise
-    fn and(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn or(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn xor(self,rhs:Self)->Self {
-        todo!();
-    }
-    fn not(self)->Self {
-        todo!();
+/// A native unsigned integer type, which is a machine word of a fixed
+/// width.
+pub trait NativeWord : MachineWord {
+    /// Number of bits in a word of this type.
+    const WIDTH : usize;
+}
+
+/// Implement `MachineWord` for a native unsigned integer type.  All
+/// arithmetic wraps around on overflow.  Following the convention of
+/// the EVM, division (and remainder) by zero produces zero rather
+/// than panicking.
+macro_rules! impl_machine_word {
+    ($($t:ty),*) => {
+        $(
+            impl NativeWord for $t {
+                const WIDTH : usize = <$t>::BITS as usize;
+            }
+
+            impl MachineWord for $t {
+                // Comparators
+                fn less_than(self,rhs:Self)->Self {
+                    if self < rhs { 1 } else { 0 }
+                }
+                fn equal(self,rhs:Self)->Self {
+                    if self == rhs { 1 } else { 0 }
+                }
+                // Arithmetic
+                fn add(self,rhs:Self)->Self {
+                    self.wrapping_add(rhs)
+                }
+                fn sub(self,rhs:Self)->Self {
+                    self.wrapping_sub(rhs)
+                }
+                fn mul(self,rhs:Self)->Self {
+                    self.wrapping_mul(rhs)
+                }
+                fn div(self,rhs:Self)->Self {
+                    self.checked_div(rhs).unwrap_or(0)
+                }
+                fn rem(self,rhs:Self)->Self {
+                    self.checked_rem(rhs).unwrap_or(0)
+                }
+                fn neg(self)->Self {
+                    self.wrapping_neg()
+                }
+                // Bitwise
+                fn and(self,rhs:Self)->Self {
+                    self & rhs
+                }
+                fn or(self,rhs:Self)->Self {
+                    self | rhs
+                }
+                fn xor(self,rhs:Self)->Self {
+                    self ^ rhs
+                }
+                fn not(self)->Self {
+                    !self
+                }
+                // Truthiness
+                fn to_bool(&self)->Option<bool> {
+                    Some(*self != 0)
+                }
+                // Conversions
+                fn constant_like(&self, value: u64)->Self {
+                    value as $t
+                }
+                fn to_usize(&self)->Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+                fn to_bitvec(&self)->Option<BitVec> {
+                    Some(BitVec::from_u128(<$t>::WIDTH, *self as u128))
+                }
+            }
+        )*
     }
 }
+
+impl_machine_word!(u8,u16,u32,u64,u128,usize);
@@ -0,0 +1,78 @@
+use vcg::{Array,BitVec,MachineWord,Memory,Merge,MinimalMachineError,SymbolicMemory,Term};
+
+mod common;
+use common::{c,eval};
+
+#[test]
+fn test_01() {
+    // Read-over-write with known indices
+    let mut a = Array::new();
+    a.store(c(1), c(10));
+    a.store(c(2), c(20));
+    a.store(c(1), c(30));
+    assert_eq!(a.stores().len(), 2);
+    assert_eq!(a.select(&c(1)), c(30));
+    assert_eq!(a.select(&c(2)), c(20));
+    assert_eq!(a.select(&c(3)), c(0));
+}
+
+#[test]
+fn test_02() {
+    // Read-over-write with unknown indices
+    let (x,y) = (Term::var("x",8),Term::var("y",8));
+    let mut a = Array::new();
+    a.store(x.clone(), c(1));
+    a.store(x.clone().add(c(1)), c(2));
+    assert_eq!(a.select(&x), c(1));
+    assert_eq!(a.select(&x.clone().add(c(1))), c(2));
+    let v = a.select(&y);
+    assert_eq!(eval(&v, 5, 5), 1);
+    assert_eq!(eval(&v, 5, 6), 2);
+    assert_eq!(eval(&v, 5, 7), 0);
+    // Overwriting an unknown index
+    a.store(y.clone(), c(3));
+    assert_eq!(a.select(&y), c(3));
+    assert_eq!(eval(&a.select(&x), 5, 5), 3);
+    assert_eq!(eval(&a.select(&x), 5, 6), 1);
+}
+
+#[test]
+fn test_03() {
+    let x = Term::var("x",8);
+    let b = Term::var("b",1);
+    let mut l = Array::new();
+    l.store(c(0), c(1));
+    let mut r = l.clone();
+    l.store(x.clone(), c(2));
+    r.store(c(1), c(3));
+    let m = Array::merge(&b, &l, &r);
+    assert_eq!(m.stores()[0], (c(0),c(1)));
+    for (xv,bv,i,v) in [(1,1,1,2),(1,0,1,3),(0,1,0,2),(0,0,0,1),(4,0,4,0),(4,1,1,0)] {
+        let s = m.select(&c(i));
+        let env = |n: &str| Some(if n == "x" { BitVec::from_u64(8,xv) } else { BitVec::from_u64(1,bv) });
+        assert_eq!(s.eval(&env).unwrap().to_u64(), Some(v));
+    }
+}
+
+#[test]
+fn test_04() {
+    let x = Term::var("x",8);
+    let mut m = SymbolicMemory::<MinimalMachineError>::new().max_size(64);
+    assert_eq!(m.store(&x, 2, Term::from_u64(8,0xab)), Ok(()));
+    assert_eq!(m.size(), 0);
+    assert_eq!(m.load(&x, 2), Ok(Term::from_u64(8,0xab)));
+    assert_eq!(m.load(&x.clone().add(c(1)), 1), Ok(Term::from_u64(8,0xab)));
+    assert_eq!(m.store(&c(31), 2, c(1)), Ok(()));
+    assert_eq!(m.size(), 64);
+    assert_eq!(m.load(&c(64), 1), Err(MinimalMachineError::InvalidAddress));
+    // Merge preserves each side
+    let b = Term::var("b",1);
+    let mut n = SymbolicMemory::new();
+    assert_eq!(n.store(&c(0), 1, c(7)), Ok(()));
+    let mut j = SymbolicMemory::merge(&b, &m, &n).unwrap();
+    let v = j.load(&c(0), 1).unwrap();
+    for (xv,bv,r) in [(0xff,1,0xab),(0xfe,1,0),(0xff,0,7)] {
+        let env = |n: &str| Some(if n == "x" { BitVec::from_u64(8,xv) } else { BitVec::from_u64(1,bv) });
+        assert_eq!(v.eval(&env).unwrap().to_u64(), Some(r));
+    }
+}
@@ -0,0 +1,108 @@
+use vcg::{BitVec,MachineWord};
+
+#[test]
+fn test_01() {
+    // Wrap around at narrow widths
+    check_binop(8, 0xff, 0x1, BitVec::add, 0x0);
+    check_binop(3, 0x7, 0x2, BitVec::add, 0x1);
+    check_binop(1, 0x1, 0x1, BitVec::add, 0x0);
+}
+
+#[test]
+fn test_02() {
+    // Carry propagation across limbs
+    check_binop(128, u64::MAX as u128, 0x1, BitVec::add, 1 << 64);
+    check_binop(128, u128::MAX, 0x1, BitVec::add, 0x0);
+    check_binop(100, (1 << 100) - 1, 0x2, BitVec::add, 0x1);
+}
+
+#[test]
+fn test_03() {
+    check_binop(8, 0x10, 0x10, BitVec::mul, 0x0);
+    check_binop(8, 0x0f, 0x03, BitVec::mul, 0x2d);
+    check_binop(128, 1 << 64, 1 << 64, BitVec::mul, 0x0);
+    check_binop(128, 1 << 64, 1 << 63, BitVec::mul, 1 << 127);
+    check_binop(128, u64::MAX as u128, u64::MAX as u128, BitVec::mul,
+                (u64::MAX as u128) * (u64::MAX as u128));
+}
+
+#[test]
+fn test_04() {
+    check_binop(8, 0xff, 0x10, BitVec::div, 0x0f);
+    check_binop(8, 0xff, 0x10, BitVec::rem, 0x0f);
+    check_binop(8, 0xff, 0x81, BitVec::div, 0x01);
+    check_binop(8, 0xff, 0x81, BitVec::rem, 0x7e);
+    check_binop(128, u128::MAX, 0x3, BitVec::div, u128::MAX / 3);
+    check_binop(128, u128::MAX, 0x7, BitVec::rem, u128::MAX % 7);
+}
+
+#[test]
+fn test_05() {
+    // Division by zero
+    check_binop(8, 0x12, 0x0, BitVec::div, 0x0);
+    check_binop(8, 0x12, 0x0, BitVec::rem, 0x0);
+    check_binop(256, 0x12, 0x0, BitVec::div, 0x0);
+}
+
+#[test]
+fn test_06() {
+    check_binop(8, 0x1, 0x2, BitVec::less_than, 0x1);
+    check_binop(8, 0x2, 0x1, BitVec::less_than, 0x0);
+    check_binop(8, 0x2, 0x2, BitVec::equal, 0x1);
+    check_binop(128, 1 << 64, 0x1, BitVec::less_than, 0x0);
+    check_binop(128, 1 << 64, 1 << 64, BitVec::equal, 0x1);
+}
+
+#[test]
+fn test_07() {
+    check_binop(8, 0xf0, 0x3c, BitVec::and, 0x30);
+    check_binop(8, 0xf0, 0x3c, BitVec::or, 0xfc);
+    check_binop(8, 0xf0, 0x3c, BitVec::xor, 0xcc);
+    check_unop(8, 0xf0, BitVec::not, 0x0f);
+    check_unop(100, 0x0, BitVec::not, (1 << 100) - 1);
+}
+
+#[test]
+fn test_08() {
+    check_unop(8, 0x1, BitVec::neg, 0xff);
+    check_unop(8, 0x0, BitVec::neg, 0x0);
+    check_unop(128, 0x1, BitVec::neg, u128::MAX);
+}
+
+#[test]
+fn test_09() {
+    // 256bit words
+    let max = BitVec::ones(256);
+    let one = BitVec::from_u64(256, 1);
+    assert!(max.clone().add(one.clone()).is_zero());
+    assert_eq!(max.clone().mul(max.clone()), one);
+    assert_eq!(max.clone().div(max.clone()), one);
+    assert_eq!(max.clone().div(one.clone()), max);
+    assert_eq!(BitVec::zero(256).neg(), BitVec::zero(256));
+    assert_eq!(max.trailing_zeros(), 0);
+    assert_eq!(one.leading_zeros(), 255);
+    assert_eq!(format!("{}",max.clone().add(max)),
+               "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe");
+}
+
+#[test]
+#[should_panic]
+fn test_10() {
+    let _ = BitVec::from_u64(8, 1).add(BitVec::from_u64(16, 1));
+}
+
+fn check_binop<F>(width: usize, lhs: u128, rhs: u128, op: F, expected: u128)
+where F: Fn(BitVec,BitVec)->BitVec {
+    let l = BitVec::from_u128(width, lhs);
+    let r = BitVec::from_u128(width, rhs);
+    let v = op(l,r);
+    assert_eq!(v.width(), width);
+    assert_eq!(v, BitVec::from_u128(width, expected));
+}
+
+fn check_unop<F>(width: usize, arg: u128, op: F, expected: u128)
+where F: Fn(BitVec)->BitVec {
+    let v = op(BitVec::from_u128(width, arg));
+    assert_eq!(v.width(), width);
+    assert_eq!(v, BitVec::from_u128(width, expected));
+}
@@ -0,0 +1,97 @@
+use vcg::{BitBlaster,BitVec,BinaryOp,MachineState,MachineWord,Response,Term,UnaryOp,VecState};
+use vcg::check_validity;
+
+const WIDTH : usize = 3;
+
+#[test]
+fn test_01() {
+    // Unary operators agree with evaluation
+    for op in [UnaryOp::Neg, UnaryOp::Not, UnaryOp::NonZero] {
+        check(Term::unary(op, Term::var("x",WIDTH)));
+    }
+}
+
+#[test]
+fn test_02() {
+    // Binary operators agree with evaluation
+    let ops = [BinaryOp::LessThan, BinaryOp::Equal, BinaryOp::Add, BinaryOp::Mul, BinaryOp::Div,
+               BinaryOp::Rem, BinaryOp::And, BinaryOp::Or, BinaryOp::Xor];
+    for op in ops {
+        check(Term::binary(op, Term::var("x",WIDTH), Term::var("y",WIDTH)));
+    }
+}
+
+#[test]
+fn test_03() {
+    // Conditionals agree with evaluation
+    let x = Term::var("x",WIDTH);
+    let y = Term::var("y",WIDTH);
+    check(Term::ite(x.clone().less_than(y.clone()).nonzero(), x.clone().add(y.clone()), x.mul(y)));
+}
+
+#[test]
+fn test_04() {
+    let x = Term::var("x",8);
+    // (x / 3) * 3 + (x % 3) == x
+    let three = Term::from_u64(8,3);
+    let vc = x.clone().div(three.clone()).mul(three.clone()).add(x.clone().rem(three)).equal(x.clone());
+    assert_eq!(check_validity(&vc), Response::Unsat);
+    // x + 1 > x (does not hold for x == 255)
+    let vc = x.clone().less_than(x.clone().add(Term::from_u64(8,1)));
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert_eq!(model.get("x",8), Some(&BitVec::from_u64(8,255)));
+}
+
+#[test]
+fn test_05() {
+    // Counterexamples map back to initial states
+    let mut s : VecState<Term> = VecState::init();
+    s.push(Term::var("x",8)).unwrap();
+    s.push(Term::var("y",8)).unwrap();
+    let x = s.peek(1).unwrap().clone();
+    let y = s.peek(0).unwrap().clone();
+    // x * y == 12 && x < y ==> x < 4
+    let pre = x.clone().mul(y.clone()).equal(Term::from_u64(8,12)).nonzero().and(x.clone().less_than(y).nonzero());
+    let vc = pre.implies(x.less_than(Term::from_u64(8,4)).nonzero());
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert_eq!(model.eval(&vc), BitVec::from_u64(1,0));
+    let c = s.map(|t| model.eval(t));
+    let (x,y) = (c.peek(1).unwrap().clone(), c.peek(0).unwrap().clone());
+    assert_eq!(x.clone().mul(y.clone()), BitVec::from_u64(8,12));
+    assert_eq!(x.less_than(y), BitVec::from_u64(8,1));
+}
+
+#[test]
+fn test_06() {
+    // Variables of different widths may share a name
+    let x8 = Term::var("x",8);
+    let x4 = Term::var("x",4);
+    let mut b = BitBlaster::new();
+    b.assert(&x8.clone().equal(Term::from_u64(8,200)).nonzero());
+    b.assert(&x4.clone().equal(Term::from_u64(4,9)).nonzero());
+    let model = b.solve().unwrap();
+    assert_eq!(model.eval(&x8), BitVec::from_u64(8,200));
+    assert_eq!(model.eval(&x4), BitVec::from_u64(4,9));
+    assert_eq!(model.iter().count(), 2);
+}
+
+/// Check a term agrees with evaluation, for all values of its
+/// variables.
+fn check(t: Term) {
+    for x in 0..(1 << WIDTH) {
+        for y in 0..(1 << WIDTH) {
+            let mut b = BitBlaster::new();
+            b.assert(&Term::var("x",WIDTH).equal(Term::from_u64(WIDTH,x)).nonzero());
+            b.assert(&Term::var("y",WIDTH).equal(Term::from_u64(WIDTH,y)).nonzero());
+            let v = Term::var("v",t.width());
+            b.assert(&v.clone().equal(t.clone()).nonzero());
+            let model = b.solve().unwrap();
+            let env = |n:&str| match n {
+                "x" => Some(BitVec::from_u64(WIDTH,x)),
+                "y" => Some(BitVec::from_u64(WIDTH,y)),
+                _ => None
+            };
+            assert_eq!(model.eval(&v), t.eval(&env).unwrap(), "{} for x={},y={}", t, x, y);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Helpers shared between tests, not all of which are used by each.
+#![allow(dead_code)]
+
+use vcg::{BitVec,Interval,Term};
+
+/// Construct an 8bit constant term.
+pub fn c(v: u64) -> Term {
+    Term::from_u64(8,v)
+}
+
+/// Evaluate a term over 8bit variables, where `x` has a given value
+/// and every other variable another.
+pub fn eval(t: &Term, x: u64, y: u64) -> u64 {
+    let env = |n: &str| Some(BitVec::from_u64(8, if n == "x" { x } else { y }));
+    t.eval(&env).unwrap().to_u64().unwrap()
+}
+
+/// Construct an 8bit interval.
+pub fn interval(lo: u64, hi: u64) -> Interval {
+    Interval::new(BitVec::from_u64(8,lo), BitVec::from_u64(8,hi))
+}
@@ -0,0 +1,44 @@
+use vcg::{Bytecode,ContextError,ContextMachine,Explorer,Machine,MinimalMachineError,StackMachine,StepMachine,VecState};
+
+use Bytecode::*;
+
+#[test]
+fn test_01() {
+    let svm = StackMachine::<u8>::new(vec![Push(0x1), Push(0x0), Div]);
+    let cvm = ContextMachine::new(&svm);
+    let e = cvm.run(VecState::init()).unwrap_err();
+    assert_eq!(e, ContextError{pc: 2, instruction: Some(Div), depth: Some(2), error: MinimalMachineError::DivisionByZero});
+    assert_eq!(e.to_string(), "division by zero at pc 2 (Div) with stack depth 2");
+}
+
+#[test]
+fn test_02() {
+    // Invalid positions have no instruction
+    let svm = StackMachine::<u8>::new(vec![Push(0x1)]);
+    let cvm = ContextMachine::new(&svm);
+    let e = cvm.run(VecState::init()).unwrap_err();
+    assert_eq!(e, ContextError{pc: 1, instruction: None, depth: Some(1), error: MinimalMachineError::InvalidPC});
+    let e : Box<dyn std::error::Error> = Box::new(e);
+    assert_eq!(e.to_string(), "invalid pc at pc 1 with stack depth 1");
+    assert!(e.source().is_some());
+}
+
+#[test]
+fn test_03() {
+    // Errors from exploration carry context
+    let svm = StackMachine::<u8>::new(vec![Push(0x1), Jump(0)]);
+    let cvm = ContextMachine::new(&svm);
+    let r = Explorer::new(&cvm).run(VecState::init().max_depth(4));
+    let e = r.paths[0].result.clone().unwrap_err();
+    assert_eq!((e.pc,e.depth,e.error), (0,Some(4),MinimalMachineError::StackOverflow));
+}
+
+#[test]
+fn test_04() {
+    // Invalid positions accessed directly have no state
+    let svm = StackMachine::<u8>::new(vec![Push(0x1)]);
+    let cvm = ContextMachine::new(&svm);
+    let e = cvm.get(1).unwrap_err();
+    assert_eq!(e, ContextError{pc: 1, instruction: None, depth: None, error: MinimalMachineError::InvalidPC});
+    assert_eq!(e.to_string(), "invalid pc at pc 1");
+}
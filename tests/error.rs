@@ -0,0 +1,33 @@
+use vcg::{MachineError,MinimalMachineError,StandardMachineError};
+
+#[test]
+fn test_01() {
+    assert_eq!(MinimalMachineError::invalid_jump(3), MinimalMachineError::InvalidJump);
+    assert_eq!(MinimalMachineError::invalid_opcode(0xfe), MinimalMachineError::InvalidOpcode);
+    assert_eq!(StandardMachineError::invalid_jump(3), StandardMachineError::InvalidJump(3));
+    assert_eq!(StandardMachineError::invalid_opcode(0xfe), StandardMachineError::InvalidOpcode(0xfe));
+    assert_eq!(MinimalMachineError::invalid_local(1), MinimalMachineError::InvalidLocal);
+    assert_eq!(StandardMachineError::invalid_local(1), StandardMachineError::InvalidLocal(1));
+}
+
+#[test]
+fn test_02() {
+    assert_eq!(StandardMachineError::division_by_zero().to_string(), "division by zero");
+    assert_eq!(StandardMachineError::invalid_opcode(0xfe).to_string(), "invalid opcode 0xfe");
+    assert_eq!(StandardMachineError::invalid_jump(7).to_string(), "invalid jump destination 7");
+    let e : Box<dyn std::error::Error> = Box::new(StandardMachineError::out_of_gas());
+    assert_eq!(e.to_string(), "out of gas");
+    assert_eq!(StandardMachineError::invalid_address().to_string(), "invalid memory address");
+    assert_eq!(StandardMachineError::unknown_value().to_string(), "unknown value");
+    assert_eq!(StandardMachineError::call_depth_exceeded().to_string(), "call depth exceeded");
+    assert_eq!(StandardMachineError::invalid_local(2).to_string(), "invalid local slot 2");
+    assert_eq!(StandardMachineError::invalid_return().to_string(), "return without call");
+}
+
+#[test]
+fn test_03() {
+    assert_eq!(MinimalMachineError::division_by_zero().to_string(), "division by zero");
+    assert_eq!(MinimalMachineError::invalid_opcode(0xfe).to_string(), "invalid opcode");
+    assert_eq!(MinimalMachineError::stack_underflow().to_string(), "stack underflow");
+    assert_eq!(MinimalMachineError::call_depth_exceeded().to_string(), "call depth exceeded");
+}
@@ -0,0 +1,127 @@
+use vcg::{Branch,Branching,Explorer,Limit,Machine,MachineState,MinimalMachineError,Strategy,VecState};
+
+use Insn::*;
+
+#[test]
+fn test_01() {
+    let code = vec![Push(0x1), Return];
+    let r = Explorer::new(&ForkMachine{code}).run(VecState::init());
+    assert_eq!(results(&r.paths), vec![Ok(0x1)]);
+    assert_eq!(r.steps, 2);
+    assert_eq!(r.limit, None);
+    assert!(r.unexplored.is_empty());
+}
+
+#[test]
+fn test_02() {
+    let code = vec![Fork(3), Push(0x1), Return, Return];
+    let m = ForkMachine{code};
+    // Depth-first
+    let r = Explorer::new(&m).run(VecState::init());
+    assert_eq!(results(&r.paths), vec![Ok(0x1), Err(MinimalMachineError::StackUnderflow)]);
+    assert_eq!(r.states, 4);
+    // Breadth-first
+    let r = Explorer::new(&m).strategy(Strategy::BreadthFirst).run(VecState::init());
+    assert_eq!(results(&r.paths), vec![Err(MinimalMachineError::StackUnderflow), Ok(0x1)]);
+    assert_eq!(r.paths.iter().map(|p| p.depth).collect::<Vec<_>>(), vec![2,3]);
+}
+
+#[test]
+fn test_03() {
+    let code = vec![Fork(3), Push(0x1), Return, Return];
+    let m = ForkMachine{code};
+    let r = Explorer::new(&m).strategy(Strategy::BoundedDepth(2)).run(VecState::init());
+    assert_eq!(results(&r.paths), vec![Err(MinimalMachineError::StackUnderflow)]);
+    assert_eq!(r.unexplored.len(), 1);
+    assert_eq!(r.unexplored[0].pc(), 2);
+    assert_eq!(r.limit, None);
+}
+
+#[test]
+fn test_04() {
+    // An infinite loop
+    let code = vec![Push(0x1), Fork(0), Return];
+    let m = ForkMachine{code};
+    let r = Explorer::new(&m).max_steps(10).run(VecState::init());
+    assert_eq!(r.limit, Some(Limit::Steps));
+    assert_eq!(r.steps, 10);
+    let r = Explorer::new(&m).max_paths(3).run(VecState::init());
+    assert_eq!(r.limit, Some(Limit::Paths));
+    assert_eq!(results(&r.paths), vec![Ok(0x1), Ok(0x1), Ok(0x1)]);
+}
+
+#[test]
+fn test_05() {
+    // An exponentially branching loop
+    let code = vec![Fork(1), Fork(0), Return];
+    let m = ForkMachine{code};
+    let r = Explorer::new(&m).strategy(Strategy::BreadthFirst).max_states(4).run(VecState::init());
+    assert_eq!(r.limit, Some(Limit::States));
+    assert_eq!(r.unexplored.len(), 5);
+}
+
+fn results(paths: &[vcg::Path<u8,MinimalMachineError>]) -> Vec<Result<u8,MinimalMachineError>> {
+    paths.iter().map(|p| p.result).collect()
+}
+
+// ===================================================================
+// Machine definition
+// ===================================================================
+
+/// A simple machine which executes one instruction at a time, and
+/// can fork execution.
+pub struct ForkMachine {
+    code: Vec<Insn>
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Insn {
+    Push(u8),
+    /// Continue at both the next instruction and a given target.
+    Fork(usize),
+    Return
+}
+
+pub enum Outcome {
+    Next(Vec<VecState<u8>>),
+    Done(u8)
+}
+
+impl Branching<VecState<u8>> for Outcome {
+    type Terminal = u8;
+
+    fn branch(self) -> Branch<VecState<u8>,u8> {
+        match self {
+            Outcome::Next(states) => Branch::Continue(states),
+            Outcome::Done(v) => Branch::Terminate(v)
+        }
+    }
+}
+
+impl Machine for ForkMachine {
+    type Error = MinimalMachineError;
+    type State = VecState<u8>;
+    type Instruction = Insn;
+    type Outcome = Outcome;
+
+    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.code.get(pc).ok_or(MinimalMachineError::InvalidPC)
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        match self.get(state.pc())? {
+            Push(v) => {
+                state.push(*v)?;
+                state.goto(state.pc()+1);
+                Ok(Outcome::Next(vec![state]))
+            }
+            Fork(target) => {
+                let mut other = state.clone();
+                other.goto(*target);
+                state.goto(state.pc()+1);
+                Ok(Outcome::Next(vec![state,other]))
+            }
+            Return => Ok(Outcome::Done(state.pop()?))
+        }
+    }
+}
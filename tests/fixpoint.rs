@@ -0,0 +1,141 @@
+use vcg::{BitVec,Fixpoint,Interval,Machine,MachineState,MachineWord,MinimalMachineError};
+use vcg::{Bytecode,StackMachine,StepOutcome,Termination,VecState};
+
+mod common;
+use common::interval;
+
+use Insn::*;
+
+#[test]
+fn test_01() {
+    // Straight-line code
+    let code = vec![Push(0x1), Push(0x2), Add, Ret];
+    let r = Fixpoint::new(&IntervalMachine{code}).run(VecState::init());
+    assert!(r.stable);
+    assert_eq!(r.iterations, 4);
+    assert_eq!(r.outcomes[&3], Termination::Return(interval(0x3, 0x3)));
+    assert!(r.errors.is_empty());
+}
+
+#[test]
+fn test_02() {
+    // Simple loop: x = 0; while x < 10 { x = x + 1 }; return x;
+    let code = vec![Push(0x0), Dup, Push(0xa), Lt, JumpIf(6), Ret, Push(0x1), Add, Jump(1)];
+    let m = IntervalMachine{code};
+    let r = Fixpoint::new(&m).run(VecState::init());
+    assert!(r.stable);
+    assert_eq!(r.states[&1].peek(0).unwrap(), &interval(0x0, 0xff));
+    assert_eq!(r.outcomes[&5], Termination::Return(interval(0x0, 0xff)));
+    // Delaying widening takes (many) more iterations
+    let r2 = Fixpoint::new(&m).widening_delay(10).run(VecState::init());
+    assert!(r2.stable);
+    assert_eq!(r2.states, r.states);
+    assert!(r2.iterations > r.iterations);
+}
+
+#[test]
+fn test_03() {
+    // Loop which grows the stack
+    let code = vec![Push(0x0), Push(0x1), Push(0x1), JumpIf(1), Ret];
+    let r = Fixpoint::new(&IntervalMachine{code}).run(VecState::init());
+    assert!(r.stable);
+    assert!(r.inconsistent.contains(&1));
+    // Stack underflow is detected
+    let code = vec![Push(0x0), Add, Ret];
+    let r = Fixpoint::new(&IntervalMachine{code}).run(VecState::init());
+    assert_eq!(r.errors[&1], MinimalMachineError::StackUnderflow);
+}
+
+#[test]
+fn test_04() {
+    let code = vec![Push(0x0), Push(0x1), Add, Jump(1)];
+    let r = Fixpoint::new(&IntervalMachine{code}).widening_delay(1000).max_iterations(10).run(VecState::init());
+    assert!(!r.stable);
+    assert_eq!(r.iterations, 10);
+}
+
+#[test]
+fn test_05() {
+    // Subroutine reached from two call sites
+    let code = vec![Bytecode::Call(4,0), Bytecode::Call(4,0), Bytecode::Push(interval(0x1,0x1)),
+                    Bytecode::Return, Bytecode::Ret];
+    let r = Fixpoint::new(&StackMachine::new(code)).run(VecState::init());
+    assert!(r.stable);
+    assert!(r.inconsistent.contains(&4));
+    assert!(r.errors.is_empty());
+}
+
+// ===================================================================
+// Machine definition
+// ===================================================================
+
+/// A simple machine which executes over intervals.
+pub struct IntervalMachine {
+    code: Vec<Insn>
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Insn {
+    Push(u8),
+    Dup,
+    Add,
+    Lt,
+    /// Branch to a given target if the top of the stack is non-zero.
+    JumpIf(usize),
+    Jump(usize),
+    Ret
+}
+
+impl Machine for IntervalMachine {
+    type Error = MinimalMachineError;
+    type State = VecState<Interval>;
+    type Instruction = Insn;
+    type Outcome = StepOutcome<Self::State,Interval>;
+
+    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.code.get(pc).ok_or(MinimalMachineError::InvalidPC)
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        let pc = state.pc();
+        match self.get(pc)? {
+            Push(v) => {
+                state.push(interval(*v as u64, *v as u64))?;
+            }
+            Dup => {
+                let v = state.peek(0)?.clone();
+                state.push(v)?;
+            }
+            Add => {
+                let r = state.pop()?;
+                let l = state.pop()?;
+                state.push(l.add(r))?;
+            }
+            Lt => {
+                let r = state.pop()?;
+                let l = state.pop()?;
+                state.push(l.less_than(r))?;
+            }
+            JumpIf(target) => {
+                let c = state.pop()?;
+                let mut other = state.clone();
+                other.goto(*target);
+                state.goto(pc+1);
+                return Ok(if !c.contains(&BitVec::zero(8)) {
+                    StepOutcome::Continue(other)
+                } else if c.as_constant().is_some() {
+                    StepOutcome::Continue(state)
+                } else {
+                    StepOutcome::Fork(vec![state,other])
+                });
+            }
+            Jump(target) => {
+                state.goto(*target);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Ret => { return Ok(StepOutcome::Return(state.pop()?)); }
+        }
+        state.goto(pc+1);
+        Ok(StepOutcome::Continue(state))
+    }
+}
@@ -0,0 +1,80 @@
+use vcg::{Bytecode,GasMachine,GasState,MachineWord,MinimalMachineError,Response,StackGasSchedule,StackMachine};
+use vcg::{StepMachine,StepOutcome,SymbolicMemory,Term,VecState,check_validity,generate_vc};
+
+use Bytecode::*;
+
+#[test]
+fn test_01() {
+    let svm = StackMachine::new(vec![Push(0x1u8), Push(0x2), Add, Return]);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    assert_eq!(gvm.run(VecState::init().gas(9)), Ok(StepOutcome::Return(0x3)));
+    assert_eq!(gvm.run(VecState::init().gas(8)), Err(MinimalMachineError::OutOfGas));
+    // Unmetered execution is never charged
+    assert_eq!(gvm.run(VecState::init()), Ok(StepOutcome::Return(0x3)));
+}
+
+#[test]
+fn test_02() {
+    // Arbitrary cost models
+    let svm = StackMachine::new(vec![Push(0x1u8), Jump(0)]);
+    let gvm = GasMachine::new(&svm, |_: &Bytecode<u8>, _: &VecState<u8>| 1);
+    assert_eq!(gvm.run(VecState::init().gas(100)), Err(MinimalMachineError::OutOfGas));
+    let gvm = GasMachine::new(&svm, |_: &Bytecode<u8>, _: &VecState<u8>| 1000);
+    assert_eq!(gvm.run(VecState::init().gas(0xff)), Err(MinimalMachineError::OutOfGas));
+}
+
+#[test]
+fn test_03() {
+    // Memory expansion is charged
+    let svm = StackMachine::new(vec![Push(0x1u16), Push(0x0), MStore(1), Push(0x1), Push(0x20), MStore(1), Halt]);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let mut gas = Vec::new();
+    let o = gvm.run_with(VecState::init().gas(100), |s| gas.push(*s.gas().unwrap()));
+    assert_eq!(o, Ok(StepOutcome::Halt));
+    assert_eq!(gas, vec![100,97,94,88,85,82,76]);
+}
+
+#[test]
+fn test_04() {
+    // Never runs out of gas, given at least 9 units
+    let g = Term::var("g",8);
+    let svm = StackMachine::new(vec![
+        Push(Term::var("x",8)),
+        Push(Term::from_u64(8,2)),
+        Add,
+        Return
+    ]);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let vc = generate_vc(&gvm, VecState::init().gas(g.clone())).unwrap();
+    let sufficient = |n| g.clone().less_than(Term::from_u64(8,n)).nonzero().not();
+    assert_eq!(check_validity(&sufficient(9).implies(vc.clone())), Response::Unsat);
+    let Response::Sat(model) = check_validity(&sufficient(8).implies(vc)) else { panic!() };
+    assert_eq!(model.get("g",8).unwrap().to_u64(), Some(8));
+}
+
+#[test]
+fn test_05() {
+    // Costs which do not fit into a word always exceed the gas
+    let svm = StackMachine::new(vec![Halt]);
+    let gvm = GasMachine::new(&svm, |_: &Bytecode<u8>, _: &VecState<u8>| 300);
+    assert_eq!(gvm.run(VecState::init().gas(0xff)), Err(MinimalMachineError::OutOfGas));
+    let svm = StackMachine::new(vec![Halt]);
+    let gvm = GasMachine::new(&svm, |_: &Bytecode<Term>, _: &VecState<Term>| 300);
+    let vc = generate_vc(&gvm, VecState::init().gas(Term::var("g",8))).unwrap();
+    assert_eq!(vc, Term::bool(false));
+}
+
+#[test]
+fn test_06() {
+    // Accesses at unknown addresses are charged the worst case
+    let g = Term::var("g",8);
+    let svm = StackMachine::<Term,SymbolicMemory<_>>::new(vec![Push(Term::var("x",8)), MLoad(1), Halt]);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let init = VecState::init().with_memory(SymbolicMemory::new().max_size(0x100)).gas(g.clone());
+    let vc = generate_vc(&gvm, init).unwrap();
+    // Push (3) and MLoad (3), along with expanding to eight words (24)
+    let sufficient = |n| g.clone().less_than(Term::from_u64(8,n)).nonzero().not();
+    assert_eq!(check_validity(&sufficient(30).implies(vc.clone())), Response::Unsat);
+    let Response::Sat(model) = check_validity(&sufficient(29).implies(vc)) else { panic!() };
+    assert_eq!(model.get("g",8).unwrap().to_u64(), Some(29));
+}
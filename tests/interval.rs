@@ -0,0 +1,108 @@
+use vcg::{BitVec,Interval,Lattice,MachineWord};
+
+#[test]
+fn test_01() {
+    let i = interval(8, 0x1, 0x3).add(interval(8, 0x10, 0x20));
+    assert_eq!(i, interval(8, 0x11, 0x23));
+    // Both bounds wrap
+    let i = interval(8, 0xf0, 0xf8).add(interval(8, 0x10, 0x20));
+    assert_eq!(i, interval(8, 0x00, 0x18));
+    // One bound wraps
+    let i = interval(8, 0x01, 0xf8).add(interval(8, 0x10, 0x20));
+    assert!(i.is_full());
+}
+
+#[test]
+fn test_02() {
+    assert_eq!(interval(8, 0x2, 0x3).mul(interval(8, 0x4, 0x5)), interval(8, 0x8, 0xf));
+    assert!(interval(8, 0x2, 0x30).mul(interval(8, 0x4, 0x50)).is_full());
+    assert_eq!(interval(8, 0x10, 0x20).div(interval(8, 0x2, 0x4)), interval(8, 0x4, 0x10));
+    assert_eq!(interval(8, 0x10, 0x20).div(interval(8, 0x0, 0x4)), interval(8, 0x0, 0x20));
+    assert_eq!(interval(8, 0x10, 0x20).rem(interval(8, 0x0, 0x0)), interval(8, 0x0, 0x0));
+    assert_eq!(interval(8, 0x1, 0x3).rem(interval(8, 0x4, 0x8)), interval(8, 0x1, 0x3));
+}
+
+#[test]
+fn test_03() {
+    let one = interval(8, 1, 1);
+    let zero = interval(8, 0, 0);
+    assert_eq!(interval(8, 0x1, 0x3).less_than(interval(8, 0x4, 0x8)), one);
+    assert_eq!(interval(8, 0x4, 0x8).less_than(interval(8, 0x1, 0x4)), zero);
+    assert_eq!(interval(8, 0x1, 0x4).less_than(interval(8, 0x4, 0x8)), interval(8, 0, 1));
+    assert_eq!(interval(8, 0x1, 0x3).equal(interval(8, 0x4, 0x8)), zero);
+    assert_eq!(interval(8, 0x4, 0x4).equal(interval(8, 0x4, 0x4)), one);
+}
+
+#[test]
+fn test_04() {
+    let a = interval(8, 0x1, 0x3);
+    let b = interval(8, 0x5, 0x8);
+    let e = Interval::empty(8);
+    assert_eq!(a.join(&b), interval(8, 0x1, 0x8));
+    assert_eq!(a.join(&e), a);
+    assert!(a.meet(&b).is_empty());
+    assert_eq!(a.meet(&interval(8, 0x2, 0x8)), interval(8, 0x2, 0x3));
+    assert_eq!(a.widen(&interval(8, 0x1, 0x4)), interval(8, 0x1, 0xff));
+    assert_eq!(a.widen(&interval(8, 0x0, 0x3)), interval(8, 0x0, 0x3));
+    assert_eq!(a.widen(&interval(8, 0x2, 0x2)), a);
+    assert!(e.clone().add(a).is_empty());
+}
+
+#[test]
+fn test_05() {
+    // Exhaustively check soundness of every transfer function for
+    // every pair of 3bit intervals.
+    let ops : Vec<fn(Interval,Interval)->Interval> = vec![
+        Interval::less_than, Interval::equal, Interval::add, Interval::sub, Interval::mul,
+        Interval::div, Interval::rem, Interval::and, Interval::or, Interval::xor
+    ];
+    let cops : Vec<fn(BitVec,BitVec)->BitVec> = vec![
+        BitVec::less_than, BitVec::equal, BitVec::add, BitVec::sub, BitVec::mul,
+        BitVec::div, BitVec::rem, BitVec::and, BitVec::or, BitVec::xor
+    ];
+    let intervals = all_intervals(3);
+    for (op,cop) in ops.iter().zip(cops.iter()) {
+        for l in &intervals {
+            for r in &intervals {
+                let i = op(l.clone(),r.clone());
+                for x in values(l) {
+                    for y in values(r) {
+                        let v = cop(x.clone(),y.clone());
+                        assert!(i.contains(&v), "{} not in {} (from {} and {})", v, i, l, r);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_06() {
+    for i in all_intervals(3) {
+        for x in values(&i) {
+            assert!(i.clone().neg().contains(&x.clone().neg()));
+            assert!(i.clone().not().contains(&x.not()));
+        }
+    }
+}
+
+fn interval(width: usize, lo: u64, hi: u64) -> Interval {
+    Interval::new(BitVec::from_u64(width,lo), BitVec::from_u64(width,hi))
+}
+
+fn all_intervals(width: usize) -> Vec<Interval> {
+    let n = 1u64 << width;
+    let mut r = Vec::new();
+    for lo in 0..n {
+        for hi in lo..n {
+            r.push(interval(width,lo,hi));
+        }
+    }
+    r
+}
+
+fn values(i: &Interval) -> Vec<BitVec> {
+    let lo = i.lo().to_u64().unwrap();
+    let hi = i.hi().to_u64().unwrap();
+    (lo..=hi).map(|v| BitVec::from_u64(i.width(),v)).collect()
+}
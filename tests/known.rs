@@ -0,0 +1,107 @@
+use vcg::{BitVec,KnownBits,MachineWord};
+
+#[test]
+fn test_01() {
+    // Alignment is preserved by addition and multiplication
+    let x = known(8, "??????00");
+    let y = known(8, "?????100");
+    assert_eq!(format!("{}", x.clone().add(y.clone())), "0b??????00");
+    assert_eq!(format!("{}", x.mul(y)), "0b????0000");
+}
+
+#[test]
+fn test_02() {
+    let x = known(8, "1010????");
+    assert_eq!(format!("{}", x.clone().and(constant(8,0xf0))), "0b10100000");
+    assert_eq!(format!("{}", x.clone().or(constant(8,0x0f))), "0b10101111");
+    assert_eq!(format!("{}", x.clone().xor(constant(8,0xff))), "0b0101????");
+    assert_eq!(format!("{}", x.not()), "0b0101????");
+}
+
+#[test]
+fn test_03() {
+    let x = known(8, "0000????");
+    assert_eq!(x.clone().less_than(constant(8,0x10)), constant(8,1));
+    assert_eq!(x.clone().equal(constant(8,0x10)), constant(8,0));
+    assert_eq!(format!("{}", x.clone().equal(constant(8,0x01))), "0b0000000?");
+    assert_eq!(format!("{}", x.rem(constant(8,0x4))), "0b000000??");
+}
+
+#[test]
+fn test_04() {
+    assert_eq!(constant(8,0x12).add(constant(8,0xff)), constant(8,0x11));
+    assert_eq!(constant(8,0x12).div(constant(8,0x0)), constant(8,0x0));
+    assert_eq!(constant(8,0x1).neg(), constant(8,0xff));
+    assert_eq!(constant(8,0x12).as_constant(), Some(&BitVec::from_u64(8,0x12)));
+    assert!(KnownBits::empty(8).add(constant(8,0x1)).is_empty());
+}
+
+#[test]
+fn test_05() {
+    // Exhaustively check soundness of every transfer function for
+    // every pair of 3bit words.
+    let ops : Vec<fn(KnownBits,KnownBits)->KnownBits> = vec![
+        KnownBits::less_than, KnownBits::equal, KnownBits::add, KnownBits::sub, KnownBits::mul,
+        KnownBits::div, KnownBits::rem, KnownBits::and, KnownBits::or, KnownBits::xor
+    ];
+    let cops : Vec<fn(BitVec,BitVec)->BitVec> = vec![
+        BitVec::less_than, BitVec::equal, BitVec::add, BitVec::sub, BitVec::mul,
+        BitVec::div, BitVec::rem, BitVec::and, BitVec::or, BitVec::xor
+    ];
+    let words = all_words(3);
+    for (op,cop) in ops.iter().zip(cops.iter()) {
+        for l in &words {
+            for r in &words {
+                let k = op(l.clone(),r.clone());
+                for x in values(l) {
+                    for y in values(r) {
+                        let v = cop(x.clone(),y.clone());
+                        assert!(k.contains(&v), "{} not in {} (from {} and {})", v, k, l, r);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_06() {
+    for k in all_words(3) {
+        for x in values(&k) {
+            assert!(k.clone().neg().contains(&x.clone().neg()));
+            assert!(k.clone().not().contains(&x.not()));
+        }
+    }
+}
+
+fn constant(width: usize, v: u64) -> KnownBits {
+    KnownBits::constant(BitVec::from_u64(width,v))
+}
+
+/// Construct a word from a string of `0`, `1` and `?` (most
+/// significant bit first).
+fn known(width: usize, s: &str) -> KnownBits {
+    let mut zeros = BitVec::zero(width);
+    let mut ones = BitVec::zero(width);
+    for (i,c) in s.chars().rev().enumerate() {
+        match c {
+            '0' => zeros.set_bit(i,true),
+            '1' => ones.set_bit(i,true),
+            _ => {}
+        }
+    }
+    KnownBits::new(zeros,ones)
+}
+
+fn all_words(width: usize) -> Vec<KnownBits> {
+    let mut r = vec![String::new()];
+    for _ in 0..width {
+        r = r.iter().flat_map(|s| ["0","1","?"].map(|c| format!("{}{}",s,c))).collect();
+    }
+    r.iter().map(|s| known(width,s)).collect()
+}
+
+fn values(k: &KnownBits) -> Vec<BitVec> {
+    let n = 1u64 << k.width();
+    (0..n).map(|v| BitVec::from_u64(k.width(),v)).filter(|v| k.contains(v)).collect()
+}
@@ -0,0 +1,73 @@
+use vcg::{BitVec,Interval,KnownBits,Lattice,MachineState,VecState};
+
+mod common;
+use common::interval;
+
+#[test]
+fn test_01() {
+    let a = interval(0x1, 0x3);
+    let b = interval(0x2, 0x8);
+    assert!(a.bottom().leq(&a));
+    assert!(a.leq(&a.top()));
+    assert!(a.leq(&a.join(&b)));
+    assert!(b.leq(&a.join(&b)));
+    assert!(a.meet(&b).leq(&a));
+    assert!(!a.leq(&b));
+    assert!(!a.leq(&a.bottom()));
+}
+
+#[test]
+fn test_02() {
+    // Narrowing recovers bounds lost through widening
+    let a = interval(0x1, 0x3);
+    let w = a.widen(&interval(0x1, 0x4));
+    assert_eq!(w, interval(0x1, 0xff));
+    assert_eq!(w.narrow(&interval(0x1, 0x10)), interval(0x1, 0x10));
+    assert_eq!(a.narrow(&interval(0x0, 0x10)), a);
+}
+
+#[test]
+fn test_03() {
+    let a = KnownBits::constant(BitVec::from_u64(8, 0b1100));
+    let b = KnownBits::constant(BitVec::from_u64(8, 0b1000));
+    let c = a.join(&b);
+    assert_eq!(format!("{}", c), "0b00001?00");
+    assert!(a.leq(&c));
+    assert!(b.leq(&c));
+    assert!(!c.leq(&a));
+    assert!(a.meet(&b).leq(&a.bottom()));
+    assert_eq!(c.meet(&a), a);
+    assert!(c.leq(&c.top()));
+    assert_eq!(c.widen(&a), c);
+}
+
+#[test]
+fn test_04() {
+    // States are merged pointwise
+    let s1 = state(&[interval(0x1, 0x1), interval(0x5, 0x6)]);
+    let s2 = state(&[interval(0x3, 0x3), interval(0x4, 0x4)]);
+    let s3 = s1.join(&s2);
+    assert_eq!(s3.size(), 2);
+    assert_eq!(s3.peek(0).unwrap(), &interval(0x4, 0x6));
+    assert_eq!(s3.peek(1).unwrap(), &interval(0x1, 0x3));
+    assert!(s1.leq(&s3));
+    assert!(s2.leq(&s3));
+    assert!(!s3.leq(&s1));
+    assert_eq!(s1.meet(&s3), s1);
+    assert!(s1.bottom().leq(&s2));
+    assert!(s1.top().peek(0).unwrap().is_full());
+}
+
+#[test]
+#[should_panic]
+fn test_05() {
+    let s1 = state(&[interval(0x1, 0x1)]);
+    let s2 = state(&[interval(0x1, 0x1), interval(0x1, 0x1)]);
+    let _ = s1.join(&s2);
+}
+
+fn state(items: &[Interval]) -> VecState<Interval> {
+    let mut s = VecState::init();
+    for i in items { s.push(i.clone()).unwrap(); }
+    s
+}
@@ -0,0 +1,101 @@
+use vcg::{BitVec,ConcreteMemory,EvmExpansionCost,ExpansionCost,Interval,Lattice,Memory,MinimalMachineError,WordMemory};
+use vcg::{expanded_size,DEFAULT_MAX_MEMORY};
+
+mod common;
+use common::interval;
+
+type Concrete<T> = ConcreteMemory<T,MinimalMachineError>;
+type Words<T> = WordMemory<T,MinimalMachineError>;
+
+#[test]
+fn test_01() {
+    // Round trip at different widths
+    let mut m = Concrete::<u32>::new();
+    assert_eq!(m.store(&0, 4, 0x1234_5678), Ok(()));
+    assert_eq!(m.size(), 32);
+    assert_eq!(&m.bytes()[0..4], &[0x12,0x34,0x56,0x78]);
+    assert_eq!(m.load(&0, 4), Ok(0x1234_5678));
+    assert_eq!(m.load(&1, 2), Ok(0x3456));
+    assert_eq!(m.load(&2, 4), Ok(0x5678_0000));
+    // Only least significant bytes are stored
+    assert_eq!(m.store(&8, 1, 0xabcd), Ok(()));
+    assert_eq!(m.load(&8, 1), Ok(0xcd));
+}
+
+#[test]
+fn test_02() {
+    // Loading beyond the word keeps the least significant bytes
+    let mut m = Concrete::<u8>::new();
+    assert_eq!(m.store(&0, 1, 0xff), Ok(()));
+    assert_eq!(m.store(&1, 1, 0x7f), Ok(()));
+    assert_eq!(m.load(&0, 2), Ok(0x7f));
+    assert_eq!(m.store(&2, 2, 0xab), Ok(()));
+    assert_eq!(&m.bytes()[2..4], &[0x00,0xab]);
+}
+
+#[test]
+fn test_03() {
+    // Accesses beyond the maximum size fail
+    let mut m = Concrete::<u32>::new().max_size(64);
+    assert_eq!(m.store(&60, 4, 1), Ok(()));
+    assert_eq!(m.size(), 64);
+    assert_eq!(m.store(&61, 4, 1), Err(MinimalMachineError::InvalidAddress));
+    assert_eq!(m.load(&64, 1), Err(MinimalMachineError::InvalidAddress));
+    assert_eq!(m.size(), 64);
+}
+
+#[test]
+fn test_04() {
+    // Addresses must be known
+    let mut m = Concrete::<Interval>::new();
+    let a = Interval::new(BitVec::from_u64(8,0), BitVec::from_u64(8,1));
+    assert_eq!(m.load(&a, 1), Err(MinimalMachineError::InvalidAddress));
+    // Stored values must be known
+    let mut m = Concrete::<Interval>::new();
+    let z = Interval::new(BitVec::from_u64(8,0), BitVec::from_u64(8,0));
+    assert_eq!(m.store(&z, 1, a.clone()), Err(MinimalMachineError::UnknownValue));
+    assert_eq!(m.size(), 0);
+    let mut m = Words::<Interval>::new();
+    assert_eq!(m.load(&a, 1), Err(MinimalMachineError::InvalidAddress));
+}
+
+#[test]
+fn test_05() {
+    let mut m = Words::<u16>::new();
+    assert_eq!(m.store(&3, 2, 0xbeef), Ok(()));
+    assert_eq!(m.get(3), Some(&0xbe));
+    assert_eq!(m.get(4), Some(&0xef));
+    assert_eq!(m.get(5), None);
+    assert_eq!(m.load(&2, 2), Ok(0x00be));
+    assert_eq!(m.load(&4, 2), Ok(0xef00));
+    assert_eq!(m.size(), 32);
+}
+
+#[test]
+fn test_06() {
+    // Abstract values held in memory
+    let mut l = Words::<Interval>::new();
+    let mut r = Words::<Interval>::new();
+    let zero = interval(0,0);
+    l.store(&zero, 1, interval(1,1)).unwrap();
+    r.store(&zero, 1, interval(3,3)).unwrap();
+    r.store(&interval(1,1), 1, interval(2,2)).unwrap();
+    let mut j = l.join(&r);
+    assert!(l.leq(&j) && r.leq(&j));
+    assert_eq!(j.load(&zero, 1), Ok(interval(1,3)));
+    // Byte never written in l is zero
+    assert_eq!(j.load(&interval(1,1), 1), Ok(interval(0,2)));
+}
+
+#[test]
+fn test_07() {
+    assert_eq!(expanded_size(0, 0, 0), 0);
+    assert_eq!(expanded_size(0, 0, 1), 32);
+    assert_eq!(expanded_size(0, 31, 2), 64);
+    assert_eq!(expanded_size(64, 0, 32), 64);
+    assert!(expanded_size(0, usize::MAX, 1) > DEFAULT_MAX_MEMORY);
+    let c = EvmExpansionCost;
+    assert_eq!(c.expansion_cost(0, 32), 3);
+    assert_eq!(c.expansion_cost(32, 32), 0);
+    assert_eq!(c.expansion_cost(0, 32 * 1024), 3 * 1024 + 2048);
+}
@@ -0,0 +1,87 @@
+use vcg::{Lit,Solver};
+
+#[test]
+fn test_01() {
+    let mut s = Solver::new();
+    let a = s.new_var();
+    let b = s.new_var();
+    assert!(s.add_clause(&[a, b]));
+    assert!(s.add_clause(&[!a, b]));
+    assert!(s.solve());
+    assert!(s.model(b.var()));
+    assert!(!s.add_clause(&[!b]));
+    assert!(!s.solve());
+}
+
+#[test]
+fn test_02() {
+    // Tautologies and duplicates are permitted
+    let mut s = Solver::new();
+    let a = s.new_var();
+    assert!(s.add_clause(&[a, !a]));
+    assert!(s.add_clause(&[!a, !a]));
+    assert!(s.solve());
+    assert!(!s.model(a.var()));
+    assert!(!s.add_clause(&[]));
+}
+
+#[test]
+fn test_03() {
+    // Pigeonhole: n+1 pigeons cannot fit into n holes.
+    for n in 1..6 {
+        assert!(!pigeonhole(n+1, n).solve());
+        assert!(pigeonhole(n, n).solve());
+    }
+}
+
+#[test]
+fn test_04() {
+    // Random 3-SAT instances, checked against brute force.
+    let mut seed = 12345u64;
+    for i in 0..200 {
+        let nvars = 3 + (i % 8);
+        let nclauses = nvars * 4;
+        let clauses : Vec<Vec<(usize,bool)>> = (0..nclauses).map(|_| {
+            (0..3).map(|_| ((next(&mut seed) as usize) % nvars, next(&mut seed) & 1 == 0)).collect()
+        }).collect();
+        let mut s = Solver::new();
+        let vars : Vec<Lit> = (0..nvars).map(|_| s.new_var()).collect();
+        for c in &clauses {
+            let lits : Vec<Lit> = c.iter().map(|&(v,p)| if p { vars[v] } else { !vars[v] }).collect();
+            s.add_clause(&lits);
+        }
+        let sat = s.solve();
+        let expected = (0..1u32 << nvars).any(|m| satisfies(&clauses, |v| m & (1 << v) != 0));
+        assert_eq!(sat, expected);
+        if sat {
+            assert!(satisfies(&clauses, |v| s.model(vars[v].var())));
+        }
+    }
+}
+
+fn pigeonhole(pigeons: usize, holes: usize) -> Solver {
+    let mut s = Solver::new();
+    let p : Vec<Vec<Lit>> = (0..pigeons).map(|_| (0..holes).map(|_| s.new_var()).collect()).collect();
+    for row in &p {
+        s.add_clause(row);
+    }
+    // No two pigeons share a hole
+    for (i,pi) in p.iter().enumerate() {
+        for pj in &p[i+1..] {
+            for (&a,&b) in pi.iter().zip(pj) {
+                s.add_clause(&[!a, !b]);
+            }
+        }
+    }
+    s
+}
+
+fn satisfies<F:Fn(usize)->bool>(clauses: &[Vec<(usize,bool)>], model: F) -> bool {
+    clauses.iter().all(|c| c.iter().any(|&(v,p)| model(v) == p))
+}
+
+/// Simple linear congruential generator.
+fn next(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *seed >> 33
+}
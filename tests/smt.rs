@@ -0,0 +1,123 @@
+use vcg::{BitVec,MachineState,MachineWord,Model,Response,Script,SmtError,Term,VecState};
+use vcg::{parse_response,validity_query};
+
+#[test]
+fn test_01() {
+    // Shared subterms are defined once
+    let x = Term::var("x",8);
+    let y = x.clone().add(Term::from_u64(8,1));
+    let t = y.clone().mul(y.clone()).equal(Term::from_u64(8,4)).nonzero();
+    let mut script = Script::new();
+    script.assert(&t);
+    let s = script.to_string();
+    assert!(s.starts_with("(set-logic QF_BV)"));
+    assert_eq!(s.matches("(declare-const x@8 (_ BitVec 8))").count(), 1);
+    assert_eq!(s.matches("(bvadd x@8 #x01)").count(), 1);
+    assert!(s.contains(&format!("(bvmul |%t{}| |%t{}|)", y.id(), y.id())));
+    assert!(s.contains(&format!("(assert (= |%t{}| #b1))", t.id())));
+}
+
+#[test]
+fn test_02() {
+    // Division by zero is encoded explicitly
+    let x = Term::var("x",3);
+    let y = Term::var("y z",3);
+    let mut script = Script::new();
+    let e = script.define(&x.div(y));
+    assert!(e.starts_with("|%t"));
+    let s = script.to_string();
+    assert!(s.contains("(declare-const |y z@3| (_ BitVec 3))"));
+    assert!(s.contains("(ite (= |y z@3| #b000) #b000 (bvudiv x@3 |y z@3|))"));
+}
+
+#[test]
+fn test_03() {
+    let vc = Term::var("x",8).less_than(Term::from_u64(8,20));
+    let s = validity_query(&vc);
+    assert!(s.contains("(bvnot "));
+    assert!(s.ends_with("(check-sat)\n(get-model)\n"));
+}
+
+#[test]
+fn test_04() {
+    assert_eq!(parse_response("unsat\n"), Ok(Response::Unsat));
+    assert_eq!(parse_response("unknown"), Ok(Response::Unknown));
+    assert!(matches!(parse_response("(error \"line 1\")"), Err(SmtError::SolverError(_))));
+    assert_eq!(parse_response("sat\n(\n"), Err(SmtError::UnexpectedEnd));
+    // Current model format
+    let r = parse_response("sat\n(\n  (define-fun x () (_ BitVec 8)\n    #x14)\n  (define-fun |y z| () (_ BitVec 3) #b101)\n)\n");
+    let mut m = Model::new();
+    m.insert("x", BitVec::from_u64(8,20));
+    m.insert("y z", BitVec::from_u64(3,5));
+    assert_eq!(r, Ok(Response::Sat(m.clone())));
+    // Older model format
+    let r = parse_response("sat (model (define-fun x () (_ BitVec 8) (_ bv20 8)) ; comment\n (define-fun |y z| () (_ BitVec 3) #b101))");
+    assert_eq!(r, Ok(Response::Sat(m)));
+}
+
+#[test]
+fn test_05() {
+    // Counterexamples map back to initial states
+    let mut s : VecState<Term> = VecState::init();
+    s.push(Term::var("x",8)).unwrap();
+    s.push(Term::var("y",8)).unwrap();
+    let vc = s.peek(1).unwrap().clone().less_than(s.peek(0).unwrap().clone()).nonzero();
+    let r = parse_response("sat ((define-fun x () (_ BitVec 8) #x03))").unwrap();
+    let Response::Sat(model) = r else { panic!() };
+    assert_eq!(model.eval(&vc), BitVec::from_u64(1,0));
+    let c : VecState<BitVec> = s.map(|t| model.eval(t));
+    assert_eq!(c.peek(0), Ok(&BitVec::zero(8)));
+    assert_eq!(c.peek(1), Ok(&BitVec::from_u64(8,3)));
+}
+
+#[test]
+fn test_06() {
+    // Definitions cannot collide with variables
+    let x = Term::var("x",8);
+    let y = x.clone().add(Term::from_u64(8,1));
+    let id = y.id();
+    let t = Term::var(&format!("t{}", id),8);
+    let mut script = Script::new();
+    script.assert(&y.equal(t).nonzero());
+    let s = script.to_string();
+    assert!(s.contains(&format!("(declare-const t{}@8 (_ BitVec 8))", id)));
+    assert!(s.contains(&format!("(define-fun |%t{}| () (_ BitVec 8) (bvadd x@8 #x01))", id)));
+}
+
+#[test]
+fn test_07() {
+    // Variables of the same name but different widths are distinct
+    let (x8,x4) = (Term::var("x",8),Term::var("x",4));
+    let t = x8.clone().equal(Term::from_u64(8,200)).nonzero().and(x4.clone().equal(Term::from_u64(4,9)).nonzero());
+    let mut script = Script::new();
+    script.assert(&t);
+    let s = script.to_string();
+    assert!(s.contains("(declare-const x@8 (_ BitVec 8))"));
+    assert!(s.contains("(declare-const x@4 (_ BitVec 4))"));
+    // Models map back to both
+    let r = parse_response("sat ((define-fun x@8 () (_ BitVec 8) #xc8) (define-fun x@4 () (_ BitVec 4) #x9))").unwrap();
+    let Response::Sat(model) = r else { panic!() };
+    assert_eq!(model.get("x",8), Some(&BitVec::from_u64(8,200)));
+    assert_eq!(model.get("x",4), Some(&BitVec::from_u64(4,9)));
+    assert_eq!(model.eval(&t), BitVec::from_u64(1,1));
+}
+
+#[test]
+fn test_08() {
+    // Any variable name can be written
+    let mut script = Script::new();
+    for n in ["%t1", "a|b", "a\\b", "1x", "x@8"] {
+        script.define(&Term::var(n,8));
+    }
+    let s = script.to_string();
+    assert!(s.contains("(declare-const %25t1@8 (_ BitVec 8))"));
+    assert!(s.contains("(declare-const a%7cb@8 (_ BitVec 8))"));
+    assert!(s.contains("(declare-const a%5cb@8 (_ BitVec 8))"));
+    assert!(s.contains("(declare-const |1x@8| (_ BitVec 8))"));
+    assert!(s.contains("(declare-const x@8@8 (_ BitVec 8))"));
+    let r = parse_response("sat ((define-fun %25t1@8 () (_ BitVec 8) #x01) (define-fun |a%7cb@8| () (_ BitVec 8) #x02) (define-fun x@8@8 () (_ BitVec 8) #x03))").unwrap();
+    let Response::Sat(model) = r else { panic!() };
+    assert_eq!(model.get("%t1",8), Some(&BitVec::from_u64(8,1)));
+    assert_eq!(model.get("a|b",8), Some(&BitVec::from_u64(8,2)));
+    assert_eq!(model.get("x@8",8), Some(&BitVec::from_u64(8,3)));
+}
@@ -1,18 +1,21 @@
-use std::marker::PhantomData;
-use vcg::{Machine,MachineState,MachineWord,MinimalMachineError,VecState};
+use vcg::{Bytecode,Explorer,MachineState,MachineWord,MinimalMachineError,StackMachine,StepMachine};
+use vcg::{StepOutcome,Term,Termination,VecState};
+
+mod common;
+use common::interval;
 
 use Bytecode::*;
 
 #[test]
 fn test_01() {
     let bytecode = vec![
-        Push1(0x1),
-        Push1(0x2),
+        Push(0x1),
+        Push(0x2),
         Add,
         Return
     ];
 
-    check(bytecode,Ok(RuntimeOutput::Value(0x3)))
+    check(bytecode,Ok(StepOutcome::Return(0x3)))
 }
 
 #[test]
@@ -27,182 +30,323 @@ fn test_02() {
 #[test]
 fn test_03() {
     let bytecode = vec![
-        Push1(0x1),
-        Push1(0x2),
+        Push(0x1),
+        Push(0x2),
         Sub,
         Return
     ];
 
-    check(bytecode,Ok(RuntimeOutput::Value(0xff)))
+    check(bytecode,Ok(StepOutcome::Return(0xff)))
 }
 
 #[test]
 fn test_04() {
     let bytecode = vec![
-        Push1(0x1),
-        Push1(0x2),
+        Push(0x1),
+        Push(0x2),
         Eq,
         Return
     ];
 
-    check(bytecode,Ok(RuntimeOutput::Value(0x0)))
+    check(bytecode,Ok(StepOutcome::Return(0x0)))
 }
 
-fn check(code: Vec<Bytecode>, output: Result<RuntimeOutput,MinimalMachineError>) {
-    let svm = StackMachine::<u8>::new(code);
-    let init = VecState::<u8>::init();
-    let o = svm.execute(init);
-    assert_eq!(o,output);
+#[test]
+fn test_05() {
+    let bytecode = vec![
+        Push(0x1),
+        Push(0x2),
+        Neq,
+        Return
+    ];
+
+    check(bytecode,Ok(StepOutcome::Return(0x1)))
 }
 
-// ===================================================================
-// Machine definition
-// ===================================================================
+#[test]
+fn test_06() {
+    // Abstract execution
+    let bytecode = vec![
+        Push(interval(1,3)),
+        Push(interval(2,2)),
+        Mul,
+        Return
+    ];
+    let svm = StackMachine::new(bytecode);
+    let o = svm.run(VecState::init());
+    assert_eq!(o,Ok(StepOutcome::Return(interval(2,6))));
+}
 
-/// A non-trivial, yet minimalistic machine implementation.  This can
-/// describe non-trivial computation and is primarily based around a
-/// stack machine.  
-pub struct StackMachine<T:MachineWord> {
-    dummy: PhantomData<T>,
-    code: Vec<Bytecode>
+#[test]
+fn test_07() {
+    // Symbolic execution
+    let bytecode = vec![
+        Push(Term::var("x",8)),
+        Push(Term::from_u64(8,1)),
+        Sub,
+        Return
+    ];
+    let svm = StackMachine::new(bytecode);
+    let o = svm.run(VecState::init());
+    assert_eq!(o,Ok(StepOutcome::Return(Term::var("x",8).add(Term::from_u64(8,0xff)))));
 }
 
-impl<T:MachineWord> StackMachine<T> {
-    pub fn new(code: Vec<Bytecode>) -> Self {
-        Self{code,dummy: PhantomData}
-    }
+#[test]
+fn test_08() {
+    let bytecode = vec![
+        Push(0x1),
+        Jump(3),
+        Push(0x2),
+        Return
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0x1)));
+    check(vec![Push(0x1), Halt],Ok(StepOutcome::Halt));
+    let bytecode = vec![
+        Push(0x2),
+        Push(0x0),
+        JumpIf(5),
+        Push(0x1),
+        Revert,
+        Push(0x3),
+        Return
+    ];
+    check(bytecode.clone(),Ok(StepOutcome::Revert(0x1)));
+    let bytecode : Vec<_> = bytecode.into_iter().map(|b| if b == Push(0x0) { Push(0x7) } else { b }).collect();
+    check(bytecode,Ok(StepOutcome::Return(0x3)));
 }
 
-// ===================================================================
-// Bytecodes
-// ===================================================================
-
-#[derive(Clone,Debug,PartialEq)]
-pub enum Bytecode {
-    // Literals
-    Push1(u8),
-    // Comparators
-    Eq,
-    Neq,
-    Lt,
-    LtEq,
-    Gt,
-    GtEq,
-    // Arithmetic
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Rem,
-    // Control-Flow
-    Return
-}
-
-#[derive(Clone,Copy,Debug,PartialEq)]
-pub enum RuntimeOutput {
-    Error,
-    Value(u8)
-}
-
-// ===================================================================
-// Semantics
-// ===================================================================
-
-impl Machine for StackMachine<u8> {
-    type Error = MinimalMachineError;
-    type State = VecState<u8,Self::Error>;
-    type Instruction = Bytecode;
-    type Outcome = RuntimeOutput;
-
-    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
-        if pc < self.code.len() {
-            Ok(&self.code[pc])
-        } else {
-            Err(Self::Error::InvalidPC)
-        }
-    }
+#[test]
+fn test_09() {
+    check(vec![Jump(1)],Err(MinimalMachineError::InvalidJump));
+    check(vec![Push(0x1)],Err(MinimalMachineError::InvalidPC));
+    check(vec![JumpIf(0)],Err(MinimalMachineError::StackUnderflow));
+}
 
-    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
-        // Execute until nothing left.
-        loop {
-            //
-            match self.get(state.pc())? {
-                // Literals
-                Bytecode::Push1(c) => {
-                    state.push(*c)?;
-                    state.goto(state.pc()+1);        
-                }
-                Bytecode::Eq => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.equal(r))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Neq => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.equal(r).not())?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Lt => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.less_than(r))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::LtEq => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.equal(r).or(l.less_than(r)))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Gt => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(r.less_than(l))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::GtEq => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.equal(r).or(r.less_than(l)))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Add => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.wrapping_add(r))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Sub => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.wrapping_sub(r))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Mul => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l.wrapping_mul(r))?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Div => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l / r)?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Rem => {
-                    let r = state.pop()?;
-                    let l = state.pop()?;
-                    state.push(l % r)?;
-                    state.goto(state.pc()+1);                    
-                }
-                Bytecode::Return => {
-                    let v = state.pop()?;
-                    return Ok(RuntimeOutput::Value(v));
-                }
-            }            
+#[test]
+fn test_10() {
+    // Unknown conditions fork (abstractly)
+    let bytecode = vec![
+        Push(interval(0,1)),
+        JumpIf(4),
+        Push(interval(1,1)),
+        Return,
+        Push(interval(2,2)),
+        Return
+    ];
+    let svm = StackMachine::new(bytecode);
+    match svm.run(VecState::init()) {
+        Ok(StepOutcome::Fork(ss)) => {
+            assert_eq!(ss.iter().map(|s| s.pc()).collect::<Vec<_>>(), vec![2,4]);
         }
+        o => panic!("unexpected outcome {:?}", o)
     }
 }
+
+#[test]
+fn test_11() {
+    // Unknown conditions fork (symbolically)
+    let x = Term::var("x",8);
+    let bytecode = vec![
+        Push(x.clone()),
+        Push(Term::from_u64(8,10)),
+        Lt,
+        JumpIf(6),
+        Push(x.clone()),
+        Return,
+        Push(Term::from_u64(8,10)),
+        Return
+    ];
+    let svm = StackMachine::new(bytecode);
+    let r = Explorer::new(&svm).run(VecState::init());
+    let mut returns : Vec<_> = r.paths.into_iter().map(|p| p.result).collect();
+    returns.sort_by_key(|r| format!("{:?}",r));
+    assert_eq!(returns, vec![Ok(Termination::Return(Term::from_u64(8,10))), Ok(Termination::Return(x))]);
+}
+
+#[test]
+fn test_12() {
+    // Sum n + (n-1) + ... + 1
+    let bytecode = vec![
+        Push(0x0),   // sum
+        Push(0x3),   // sum n
+        // loop:
+        Dup(0),      // sum n n
+        JumpIf(5),   // sum n
+        Jump(12),
+        Dup(0),      // sum n n
+        Swap(2),     // n n sum
+        Add,         // n sum'
+        Swap(1),     // sum' n
+        Push(0x1),
+        Sub,         // sum' n-1
+        Jump(2),
+        // exit:
+        Pop,         // sum
+        Return
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0x6)));
+}
+
+#[test]
+fn test_13() {
+    check(vec![Push(0x1), Dup(1)],Err(MinimalMachineError::StackUnderflow));
+    check(vec![Push(0x1), Swap(1)],Err(MinimalMachineError::StackUnderflow));
+    check(vec![Pop],Err(MinimalMachineError::StackUnderflow));
+}
+
+#[test]
+fn test_14() {
+    // Unbounded loop eventually overflows the stack
+    let svm = StackMachine::new(vec![Push(0x1u8), Jump(0)]);
+    let o = svm.run(VecState::init().max_depth(16));
+    assert_eq!(o,Err(MinimalMachineError::StackOverflow));
+}
+
+#[test]
+fn test_15() {
+    check(vec![Push(0x1), Push(0x0), Div],Err(MinimalMachineError::DivisionByZero));
+    check(vec![Push(0x1), Push(0x0), Rem],Err(MinimalMachineError::DivisionByZero));
+    check(vec![Push(0x0), Assert],Err(MinimalMachineError::AssertionFailure));
+    check(vec![Push(0x2), Assert, Halt],Ok(StepOutcome::Halt));
+    check(vec![Invalid(0xfe)],Err(MinimalMachineError::InvalidOpcode));
+    check(vec![Jump(3)],Err(MinimalMachineError::InvalidJump));
+    check(vec![Push(0x1), JumpIf(3)],Err(MinimalMachineError::InvalidJump));
+    // Jumps not taken are not checked
+    check(vec![Push(0x0), JumpIf(9), Halt],Ok(StepOutcome::Halt));
+}
+
+#[test]
+fn test_16() {
+    let bytecode = vec![
+        Push(0x2a),
+        Push(0x4),
+        MStore(1),
+        Push(0x3),
+        MLoad(2),
+        Return
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0x2a)));
+    check(vec![Push(0x1), MStore(1)],Err(MinimalMachineError::StackUnderflow));
+    let svm = StackMachine::new(vec![Push(0x1u8), Push(0x40), MStore(1), Halt]);
+    let o = svm.run(VecState::init().max_memory(64));
+    assert_eq!(o,Err(MinimalMachineError::InvalidAddress));
+}
+
+#[test]
+fn test_17() {
+    // Symbolic values can be held in memory
+    let x = Term::var("x",8);
+    let svm = StackMachine::new(vec![
+        Push(x.clone()),
+        Push(Term::from_u64(8,0)),
+        MStore(1),
+        Push(Term::from_u64(8,0)),
+        MLoad(1),
+        Return
+    ]);
+    assert_eq!(svm.run(VecState::init()),Ok(StepOutcome::Return(x)));
+}
+
+#[test]
+fn test_18() {
+    let bytecode = vec![
+        Push(0x2a),
+        Push(0x7),
+        SStore,
+        Push(0x7),
+        SLoad,
+        Push(0x8),
+        SLoad,
+        Add,
+        Return
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0x2a)));
+    check(vec![Push(0x1), SStore],Err(MinimalMachineError::StackUnderflow));
+    // Keys must be known
+    let svm = StackMachine::new(vec![Push(interval(0,1)), SLoad, Return]);
+    assert_eq!(svm.run(VecState::init()),Err(MinimalMachineError::InvalidAddress));
+}
+
+#[test]
+fn test_19() {
+    let bytecode = vec![
+        Push(0x3),
+        Call(5,1),   // square(3)
+        Push(0x1),
+        Add,
+        Return,
+        // square(x):
+        Local(0),
+        Local(0),
+        Mul,
+        Ret
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0xa)));
+    let bytecode = vec![
+        Push(0x1),
+        Push(0x2),
+        Call(4,2),   // sub(1,2)
+        Return,
+        // sub(x,y):
+        Local(0),
+        Local(1),
+        Sub,
+        SetLocal(1),
+        Local(1),
+        Ret
+    ];
+    check(bytecode,Ok(StepOutcome::Return(0xff)));
+}
+
+#[test]
+fn test_20() {
+    check(vec![Ret],Err(MinimalMachineError::InvalidReturn));
+    check(vec![Local(0)],Err(MinimalMachineError::InvalidLocal));
+    check(vec![Call(0,1)],Err(MinimalMachineError::StackUnderflow));
+    check(vec![Call(2,0), Halt],Err(MinimalMachineError::InvalidJump));
+    check(vec![Call(1,0), Local(0)],Err(MinimalMachineError::InvalidLocal));
+    check(vec![Push(0x1), SetLocal(0)],Err(MinimalMachineError::InvalidLocal));
+    // Unbounded recursion eventually exceeds the call depth
+    let svm = StackMachine::new(vec![Push(0x1u8), Call(0,1)]);
+    let o = svm.run(VecState::init().max_calls(16));
+    assert_eq!(o,Err(MinimalMachineError::CallDepthExceeded));
+}
+
+#[test]
+fn test_21() {
+    let mut bytecode = vec![
+        Push(0x1),
+        Push(0x0),
+        SStore,
+        Call(8,0),   // f()
+        Push(0x0),
+        SLoad,
+        Return,
+        Halt,
+        // f():
+        Push(0x2),
+        Push(0x0),
+        SStore,
+        Call(13,0),  // g()
+        Ret,
+        // g():
+        Push(0x3),
+        Push(0x0),
+        SStore,
+        Revert
+    ];
+    // Only the stores of g() are rolled back
+    check(bytecode.clone(),Ok(StepOutcome::Return(0x2)));
+    // Stores of g() are rolled back along with those of f()
+    bytecode[12] = Revert;
+    check(bytecode,Ok(StepOutcome::Return(0x1)));
+    // Otherwise, execution terminates
+    check(vec![Push(0x1), Revert],Ok(StepOutcome::Revert(0x1)));
+}
+
+fn check(code: Vec<Bytecode<u8>>, output: Result<StepOutcome<VecState<u8>,u8>,MinimalMachineError>) {
+    let svm = StackMachine::<u8>::new(code);
+    let init = VecState::<u8>::init();
+    let o = svm.run(init);
+    assert_eq!(o,output);
+}
@@ -0,0 +1,109 @@
+use vcg::{Explorer,Machine,MachineState,MachineWord,MinimalMachineError};
+use vcg::{StepMachine,StepOutcome,Termination,VecState};
+
+use Insn::*;
+
+#[test]
+fn test_01() {
+    let code = vec![Push(0x3), Dec, Jnz(1), Halt];
+    let mut trace = Vec::new();
+    let m = LoopMachine{code};
+    let r = m.run_with(VecState::init(), |s| trace.push(s.pc()));
+    assert_eq!(r, Ok(StepOutcome::Halt));
+    assert_eq!(trace, vec![0,1,2,1,2,1,2,3]);
+}
+
+#[test]
+fn test_02() {
+    let code = vec![Push(0x1), Fork(3), Ret, Rev];
+    let m = LoopMachine{code};
+    let mut s1 = VecState::init();
+    s1.push(0x1).unwrap();
+    s1.goto(2);
+    let mut s2 = s1.clone();
+    s2.goto(3);
+    assert_eq!(m.run(VecState::init()), Ok(StepOutcome::Fork(vec![s1,s2])));
+}
+
+#[test]
+fn test_03() {
+    let code = vec![Push(0x1), Fork(3), Ret, Rev];
+    let m = LoopMachine{code};
+    let r = Explorer::new(&m).run(VecState::init());
+    let results : Vec<_> = r.paths.into_iter().map(|p| p.result).collect();
+    assert_eq!(results, vec![Ok(Termination::Return(0x1)), Ok(Termination::Revert(0x1))]);
+}
+
+#[test]
+fn test_04() {
+    let code = vec![Dec];
+    let m = LoopMachine{code};
+    assert_eq!(m.run(VecState::init()), Err(MinimalMachineError::StackUnderflow));
+}
+
+// ===================================================================
+// Machine definition
+// ===================================================================
+
+/// A simple machine which executes one instruction at a time.
+pub struct LoopMachine {
+    code: Vec<Insn>
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Insn {
+    Push(u8),
+    /// Decrement the top of the stack.
+    Dec,
+    /// Branch to a given target if the top of the stack is non-zero.
+    Jnz(usize),
+    /// Continue at both the next instruction and a given target.
+    Fork(usize),
+    Ret,
+    Rev,
+    Halt
+}
+
+impl Machine for LoopMachine {
+    type Error = MinimalMachineError;
+    type State = VecState<u8>;
+    type Instruction = Insn;
+    type Outcome = StepOutcome<Self::State,u8>;
+
+    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.code.get(pc).ok_or(MinimalMachineError::InvalidPC)
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        match self.get(state.pc())? {
+            Push(v) => {
+                state.push(*v)?;
+            }
+            Dec => {
+                let v = state.pop()?;
+                state.push(v.add(0xff))?;
+            }
+            Jnz(target) => {
+                if *state.peek(0)? != 0 {
+                    state.goto(*target);
+                    return Ok(StepOutcome::Continue(state));
+                }
+            }
+            Fork(target) => {
+                let mut other = state.clone();
+                other.goto(*target);
+                state.goto(state.pc()+1);
+                return Ok(StepOutcome::Fork(vec![state,other]));
+            }
+            Ret => { return Ok(StepOutcome::Return(state.pop()?)); }
+            Rev => { return Ok(StepOutcome::Revert(state.pop()?)); }
+            Halt => { return Ok(StepOutcome::Halt); }
+        }
+        state.goto(state.pc()+1);
+        Ok(StepOutcome::Continue(state))
+    }
+}
+
+impl StepMachine for LoopMachine {
+    type Value = u8;
+}
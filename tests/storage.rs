@@ -0,0 +1,96 @@
+use vcg::{BitVec,ConcreteStorage,Interval,Lattice,Merge,MinimalMachineError,Storage,SymbolicStorage,Term};
+
+mod common;
+use common::{c,eval,interval};
+
+#[test]
+fn test_01() {
+    let mut s = ConcreteStorage::<u16,MinimalMachineError>::new();
+    assert_eq!(s.load(&0x1234), Ok(0));
+    assert_eq!(s.store(0x1234, 1), Ok(()));
+    assert_eq!(s.store(0xffff, 2), Ok(()));
+    assert_eq!(s.load(&0x1234), Ok(1));
+    assert_eq!(s.get(&BitVec::from_u64(16,0xffff)), Some(&2));
+    assert_eq!(s.get(&BitVec::from_u64(16,1)), None);
+    // Roll back to checkpoint
+    let c0 = s.checkpoint();
+    assert_eq!(s.store(0x1234, 3), Ok(()));
+    let c1 = s.checkpoint();
+    assert_eq!(s.store(0x0001, 4), Ok(()));
+    assert_eq!(s.load(&0x1234), Ok(3));
+    s.commit(c1);
+    s.revert(c0);
+    assert_eq!(s.load(&0x1234), Ok(1));
+    assert_eq!(s.load(&0x0001), Ok(0));
+    assert_eq!(s.get(&BitVec::from_u64(16,1)), None);
+    // Checkpoint is discarded
+    assert_eq!(s.store(0x1234, 5), Ok(()));
+    s.revert(c0);
+    assert_eq!(s.load(&0x1234), Ok(5));
+}
+
+#[test]
+fn test_02() {
+    // Keys must be known, but values need not be
+    let mut s = ConcreteStorage::<Interval,MinimalMachineError>::new();
+    assert_eq!(s.load(&interval(0,1)), Err(MinimalMachineError::InvalidAddress));
+    assert_eq!(s.store(interval(0,1), interval(0,0)), Err(MinimalMachineError::InvalidAddress));
+    let mut t = s.clone();
+    assert_eq!(s.store(interval(1,1), interval(2,3)), Ok(()));
+    assert_eq!(t.store(interval(2,2), interval(5,5)), Ok(()));
+    let j = s.join(&t);
+    assert!(s.leq(&j) && t.leq(&j) && !j.leq(&s));
+    assert_eq!(j.load(&interval(1,1)), Ok(interval(0,3)));
+    assert_eq!(j.load(&interval(2,2)), Ok(interval(0,5)));
+}
+
+#[test]
+fn test_03() {
+    let (x,y) = (Term::var("x",8),Term::var("y",8));
+    let mut s = SymbolicStorage::<MinimalMachineError>::new();
+    assert_eq!(s.store(x.clone(), c(1)), Ok(()));
+    assert_eq!(s.store(y.clone(), c(2)), Ok(()));
+    assert_eq!(s.load(&y), Ok(c(2)));
+    assert_eq!(eval(&s.load(&x).unwrap(), 5, 5), 2);
+    assert_eq!(eval(&s.load(&x).unwrap(), 5, 6), 1);
+    let c0 = s.checkpoint();
+    assert_eq!(s.store(x.clone(), c(3)), Ok(()));
+    assert_eq!(s.load(&x), Ok(c(3)));
+    assert_eq!(s.entries().stores().len(), 2);
+    s.revert(c0);
+    assert_eq!(eval(&s.load(&x).unwrap(), 5, 5), 2);
+    assert_eq!(eval(&s.load(&x).unwrap(), 5, 6), 1);
+    // Stores are kept once committed
+    let c0 = s.checkpoint();
+    assert_eq!(s.store(x.clone(), c(3)), Ok(()));
+    s.commit(c0);
+    s.revert(c0);
+    assert_eq!(s.load(&x), Ok(c(3)));
+}
+
+#[test]
+fn test_04() {
+    let b = Term::var("b",1);
+    let mut l = ConcreteStorage::<Term,MinimalMachineError>::new();
+    let mut r = l.clone();
+    assert_eq!(l.store(c(1), c(2)), Ok(()));
+    assert_eq!(r.store(c(2), c(3)), Ok(()));
+    let m = ConcreteStorage::merge(&b, &l, &r).unwrap();
+    assert_eq!(m.load(&c(1)), Ok(Term::ite(b.clone(), c(2), c(0))));
+    assert_eq!(m.load(&c(2)), Ok(Term::ite(b.clone(), c(0), c(3))));
+    let mut l = SymbolicStorage::<MinimalMachineError>::new();
+    let mut r = l.clone();
+    assert_eq!(l.store(c(1), c(2)), Ok(()));
+    assert_eq!(r.store(c(2), c(3)), Ok(()));
+    let m = SymbolicStorage::merge(&b, &l, &r).unwrap();
+    assert_eq!(m.load(&c(1)), Ok(Term::ite(b.clone(), c(2), c(0))));
+    assert_eq!(m.load(&c(2)), Ok(Term::ite(b.clone(), c(0), c(3))));
+    // Checkpoints differ
+    l.checkpoint();
+    assert_eq!(SymbolicStorage::merge(&b, &l, &r), None);
+    r.checkpoint();
+    assert_eq!(l.store(c(1), c(4)), Ok(()));
+    let mut m = SymbolicStorage::merge(&b, &l, &r).unwrap();
+    m.revert(0);
+    assert_eq!(m.load(&c(1)), Ok(Term::ite(b, c(2), c(0))));
+}
@@ -0,0 +1,197 @@
+use vcg::{Bytecode,GasMachine,MachineWord,Response,StackGasSchedule,StackMachine,Summary,Term,VcError,VecState};
+use vcg::{check_validity,generate_modular_vc,generate_vc};
+
+mod common;
+use common::c;
+
+use Bytecode::*;
+
+#[test]
+fn test_01() {
+    // inc(inc(3)) == 5
+    let code = program(vec![
+        Push(c(3)),
+        Call(8,1),
+        Call(8,1),
+        Push(c(5)),
+        Eq,
+        Assert,
+        Halt
+    ]);
+    let vc = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[inc()]).unwrap();
+    assert_eq!(check_validity(&vc), Response::Unsat);
+}
+
+#[test]
+fn test_02() {
+    // Body does not establish postcondition
+    let code = program(vec![Push(c(3)), Call(8,1), Halt]);
+    let (a,r) = (Term::var("a",8),Term::var("r",8));
+    let summary = inc().ensures(r.equal(a.add(c(2))).nonzero());
+    let vc = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[summary]).unwrap();
+    assert!(matches!(check_validity(&vc), Response::Sat(_)));
+}
+
+#[test]
+fn test_03() {
+    // Call site does not establish precondition
+    let x = Term::var("x",8);
+    let code = program(vec![Push(x.clone()), Call(8,1), Halt]);
+    let vc = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[inc()]).unwrap();
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert!(model.eval(&x).to_u64().unwrap() >= 10);
+    // Postcondition is all that is known of the result
+    let code = program(vec![Push(c(3)), Call(8,1), Push(c(3)), Gt, Assert, Halt]);
+    let summary = inc().ensures(Term::bool(true));
+    let vc = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[summary]).unwrap();
+    assert!(matches!(check_validity(&vc), Response::Sat(_)));
+}
+
+#[test]
+fn test_04() {
+    let code = program(vec![Push(c(3)), Push(c(4)), Call(8,2), Halt]);
+    let r = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[inc()]);
+    assert_eq!(r, Err(VcError::SummaryMismatch{pc: 2}));
+}
+
+#[test]
+fn test_05() {
+    // Recursive subroutine which always returns zero
+    let (n,r) = (Term::var("n",8),Term::var("r",8));
+    let code = vec![
+        Push(Term::var("x",8)),
+        Call(6,1),
+        Push(c(0)),
+        Eq,
+        Assert,
+        Halt,
+        // f(n):
+        Local(0),
+        JumpIf(10),
+        Push(c(0)),
+        Ret,
+        Local(0),
+        Push(c(1)),
+        Sub,
+        Call(6,1),
+        Ret
+    ];
+    let summary = Summary::new(6, vec![n], vec![r.clone()]).ensures(r.equal(c(0)).nonzero());
+    let svm = StackMachine::new(code);
+    let vc = generate_modular_vc(&svm, VecState::init(), &[summary]).unwrap();
+    assert_eq!(check_validity(&vc), Response::Unsat);
+    // Cannot be verified without summaries
+    assert!(matches!(generate_vc(&svm, VecState::init()), Err(VcError::BackwardsJump{..})));
+}
+
+#[test]
+fn test_06() {
+    // Subroutines which modify storage cannot be summarised
+    let code = vec![
+        Push(c(5)),
+        Push(c(0)),
+        SStore,
+        Call(10,0),
+        Push(c(0)),
+        SLoad,
+        Push(c(5)),
+        Eq,
+        Assert,
+        Halt,
+        // f():
+        Push(c(7)),
+        Push(c(0)),
+        SStore,
+        Ret
+    ];
+    let summary = Summary::new(10, vec![], vec![]);
+    let r = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[summary]);
+    assert_eq!(r, Err(VcError::SummaryEffect{pc: 12}));
+}
+
+#[test]
+fn test_07() {
+    // Gas remaining after a call is unknown
+    let g = Term::var("g",8);
+    let code = program(vec![Push(c(3)), Call(8,1), Pop, Halt]);
+    let svm = StackMachine::new(code);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(g.clone()), &[inc()]).unwrap();
+    let sufficient = g.less_than(c(100)).nonzero().not();
+    assert!(matches!(check_validity(&sufficient.implies(vc)), Response::Sat(_)));
+    // Unless nothing further is charged
+    let code = program(vec![Push(c(3)), Call(8,1), Halt]);
+    let svm = StackMachine::new(code);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let g = Term::var("g",8);
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(g.clone()), &[inc().gas(c(17))]).unwrap();
+    let sufficient = g.less_than(c(100)).nonzero().not();
+    assert_eq!(check_validity(&sufficient.implies(vc)), Response::Unsat);
+}
+
+#[test]
+fn test_08() {
+    // Subroutines which read storage cannot be summarised
+    let code = vec![
+        Push(c(7)),
+        Push(c(0)),
+        SStore,
+        Call(5,0),
+        Halt,
+        // f():
+        Push(c(0)),
+        SLoad,
+        Push(c(0)),
+        Eq,
+        Assert,
+        Ret
+    ];
+    let summary = Summary::new(5, vec![], vec![]);
+    let r = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[summary]);
+    assert_eq!(r, Err(VcError::SummaryEffect{pc: 6}));
+}
+
+#[test]
+fn test_09() {
+    // Body is verified for any gas meeting its bound, not that of the
+    // program on entry
+    let code = program(vec![Push(c(3)), Push(c(0)), Pop, Call(8,1), Halt]);
+    let svm = StackMachine::new(code);
+    let gvm = GasMachine::new(&svm, StackGasSchedule::new());
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(c(30)), &[inc()]).unwrap();
+    assert!(matches!(check_validity(&vc), Response::Sat(_)));
+    // Call site must meet the bound
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(c(30)), &[inc().gas(c(17))]).unwrap();
+    assert!(matches!(check_validity(&vc), Response::Sat(_)));
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(c(35)), &[inc().gas(c(17))]).unwrap();
+    assert_eq!(check_validity(&vc), Response::Unsat);
+    // Bound must suffice for the body
+    let vc = generate_modular_vc(&gvm, VecState::init().gas(c(35)), &[inc().gas(c(16))]).unwrap();
+    assert!(matches!(check_validity(&vc), Response::Sat(_)));
+}
+
+#[test]
+fn test_10() {
+    // Results are distinct from variables of the program
+    let x = Term::var("r.1",8);
+    let code = program(vec![Push(c(3)), Call(8,1), Push(x.clone()), Eq, Assert, Halt]);
+    let vc = generate_modular_vc(&StackMachine::new(code), VecState::init(), &[inc()]).unwrap();
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert!(model.eval(&x).to_u64() != Some(4));
+}
+
+/// Summary of `inc(a)` which requires `a < 10`.
+fn inc() -> Summary {
+    let (a,r) = (Term::var("a",8),Term::var("r",8));
+    Summary::new(8, vec![a.clone()], vec![r.clone()])
+        .requires(a.clone().less_than(c(10)).nonzero())
+        .ensures(r.equal(a.add(c(1))).nonzero())
+}
+
+/// Append the body of `inc(a)` to a given program, such that its
+/// entry is at position `8`.
+fn program(mut code: Vec<Bytecode<Term>>) -> Vec<Bytecode<Term>> {
+    code.resize(8, Halt);
+    code.extend([Local(0), Push(c(1)), Add, Ret]);
+    code
+}
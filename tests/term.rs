@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use vcg::{BinaryOp,MachineState,MachineWord,Term,TermKind,VecState};
+
+#[test]
+fn test_01() {
+    // Hash-consing
+    let x = Term::var("x",8);
+    let y = Term::var("y",8);
+    let t1 = x.clone().add(y.clone());
+    let t2 = x.clone().add(y.clone());
+    assert_eq!(t1, t2);
+    assert_eq!(t1.id(), t2.id());
+    assert_ne!(t1, x.clone().mul(y.clone()));
+    // Commutative operations are normalised
+    assert_eq!(t1, y.add(x));
+}
+
+#[test]
+fn test_02() {
+    // Constant folding
+    let c1 = Term::from_u64(8,0xff);
+    let c2 = Term::from_u64(8,0x02);
+    assert_eq!(c1.clone().add(c2.clone()), Term::from_u64(8,0x01));
+    assert_eq!(c1.clone().mul(c2.clone()), Term::from_u64(8,0xfe));
+    assert_eq!(c2.clone().less_than(c1.clone()), Term::from_u64(8,0x01));
+    assert_eq!(c1.clone().div(Term::from_u64(8,0)), Term::from_u64(8,0));
+    assert_eq!(c1.not(), Term::from_u64(8,0));
+    assert_eq!(c2.neg(), Term::from_u64(8,0xfe));
+}
+
+#[test]
+fn test_03() {
+    // Algebraic simplification
+    let x = Term::var("x",8);
+    let zero = Term::from_u64(8,0);
+    let one = Term::from_u64(8,1);
+    assert_eq!(x.clone().add(zero.clone()), x);
+    assert_eq!(zero.clone().add(x.clone()), x);
+    assert_eq!(x.clone().mul(one.clone()), x);
+    assert_eq!(x.clone().mul(zero.clone()), zero);
+    assert_eq!(x.clone().xor(x.clone()), zero);
+    assert_eq!(x.clone().equal(x.clone()), one);
+    assert_eq!(x.clone().less_than(x.clone()), zero);
+    assert_eq!(x.clone().not().not(), x);
+    assert_eq!(x.clone().div(zero.clone()), zero);
+}
+
+#[test]
+fn test_04() {
+    let x = Term::var("x",8);
+    let t = x.clone().add(Term::from_u64(8,1)).less_than(x.clone());
+    match t.kind() {
+        TermKind::Binary(BinaryOp::LessThan,_,r) => assert_eq!(*r, x),
+        _ => panic!("unexpected term {}", t)
+    }
+    assert_eq!(t.width(), 8);
+    assert_eq!(format!("{}",t), "((x + 0x1) < x)");
+}
+
+#[test]
+fn test_05() {
+    // Symbolic execution over a stack
+    let mut state = VecState::<Term>::init();
+    state.push(Term::var("x",8)).unwrap();
+    state.push(Term::var("y",8)).unwrap();
+    state.push(Term::from_u64(8,2)).unwrap();
+    let r = state.pop().unwrap();
+    let l = state.pop().unwrap();
+    state.push(l.mul(r)).unwrap();
+    let r = state.pop().unwrap();
+    let l = state.pop().unwrap();
+    state.push(l.add(r)).unwrap();
+    assert_eq!(state.size(), 1);
+    assert_eq!(format!("{}",state.peek(0).unwrap()), "(x + (y * 0x2))");
+}
+
+#[test]
+#[should_panic]
+fn test_06() {
+    let _ = Term::var("x",8).add(Term::var("y",16));
+}
+
+#[test]
+fn test_07() {
+    // Variables of the same name but different widths are distinct
+    let x8 = Term::var("x",8);
+    let x16 = Term::var("x",16);
+    assert_ne!(x8, x16);
+    assert_eq!(x8.width(), 8);
+    assert_eq!(x16.width(), 16);
+}
+
+#[test]
+fn test_08() {
+    // Constant offsets are reassociated
+    let x = Term::var("x",8);
+    let y = x.clone().add(Term::from_u64(8,1)).add(Term::from_u64(8,2));
+    assert_eq!(y, x.clone().add(Term::from_u64(8,3)));
+    assert_eq!(y.add(Term::from_u64(8,0xfd)), x);
+}
+
+#[test]
+fn test_09() {
+    let (x,y) = (Term::var("x",8),Term::var("y",8));
+    let t = x.clone().add(y.clone()).mul(x.clone());
+    let map = HashMap::from([(x.clone(),Term::from_u64(8,2))]);
+    assert_eq!(t.substitute(&map), Term::from_u64(8,2).add(y.clone()).mul(Term::from_u64(8,2)));
+    let map = HashMap::from([(x.clone(),Term::from_u64(8,0))]);
+    assert_eq!(t.substitute(&map), Term::from_u64(8,0));
+    let map = HashMap::from([(x.clone(),y.clone()),(y.clone(),x.clone())]);
+    assert_eq!(t.substitute(&map), y.clone().add(x).mul(y));
+}
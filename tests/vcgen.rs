@@ -0,0 +1,267 @@
+use vcg::{BitVec,Machine,MachineState,MachineWord,MinimalMachineError,StepMachine};
+use vcg::{Bytecode,Response,StackMachine,StepOutcome,Storage,SymbolicMemory,SymbolicStorage,Term,VcError,VecState,Verifiable,WordMemory};
+use vcg::{check_validity,generate_vc};
+
+use Insn::*;
+
+#[test]
+fn test_01() {
+    // if x < 10 { assert x < 20 }
+    let code = vec![Dup, Push(10), Lt, JumpIf(5), Ret, Dup, Push(20), Lt, Assert, Ret];
+    let vc = generate_vc(&AssertMachine{code}, init()).unwrap();
+    assert!(valid(&vc));
+}
+
+#[test]
+fn test_02() {
+    // assert x < 20
+    let code = vec![Dup, Push(20), Lt, Assert, Ret];
+    let vc = generate_vc(&AssertMachine{code}, init()).unwrap();
+    assert!(!valid(&vc));
+    assert!(holds(&vc, 19));
+    assert!(!holds(&vc, 20));
+}
+
+#[test]
+fn test_03() {
+    // Merging states: y = (x < 10) ? 1 : 2; assert y != 0;
+    let code = vec![Dup, Push(10), Lt, JumpIf(6), Push(2), Jump(7), Push(1), Assert, Ret];
+    let vc = generate_vc(&AssertMachine{code}, init()).unwrap();
+    assert_eq!(vc, Term::bool(true));
+    // y = (x < 10) ? 1 : 0; assert y != 0;
+    let code = vec![Dup, Push(10), Lt, JumpIf(6), Push(0), Jump(7), Push(1), Assert, Ret];
+    let vc = generate_vc(&AssertMachine{code}, init()).unwrap();
+    assert!(holds(&vc, 9));
+    assert!(!holds(&vc, 10));
+}
+
+#[test]
+fn test_04() {
+    // if x < 10 { pop; pop }
+    let code = vec![Dup, Push(10), Lt, JumpIf(5), Ret, Pop, Pop, Ret];
+    let vc = generate_vc(&AssertMachine{code}, init()).unwrap();
+    assert!(!holds(&vc, 9));
+    assert!(holds(&vc, 10));
+}
+
+#[test]
+fn test_05() {
+    let code = vec![Push(1), Jump(0)];
+    let r = generate_vc(&AssertMachine{code}, init());
+    assert_eq!(r, Err(VcError::BackwardsJump{from: 1, to: 0}));
+}
+
+#[test]
+fn test_06() {
+    // Division by zero on the reference machine
+    let x = Term::var("x",8);
+    let bytecode = vec![
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Sub,
+        Bytecode::Div,
+        Bytecode::Return
+    ];
+    let vc = generate_vc(&StackMachine::new(bytecode), VecState::init()).unwrap();
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert_eq!(model.get("x",8), Some(&BitVec::from_u64(8,1)));
+    // Guarded by x > 1
+    let bytecode = vec![
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Gt,
+        Bytecode::JumpIf(5),
+        Bytecode::Halt,
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Sub,
+        Bytecode::Div,
+        Bytecode::Return
+    ];
+    let vc = generate_vc(&StackMachine::new(bytecode), VecState::init()).unwrap();
+    assert_eq!(check_validity(&vc), Response::Unsat);
+}
+
+#[test]
+fn test_07() {
+    // Memory accessed at computed addresses
+    let (x,y,z) = (Term::var("x",8),Term::var("y",8),Term::var("z",8));
+    let program = |address: &Term| vec![
+        Bytecode::Push(y.clone()),
+        Bytecode::Push(x.clone()),
+        Bytecode::MStore(1),
+        Bytecode::Push(Term::from_u64(8,7)),
+        Bytecode::Push(x.clone()),
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Add,
+        Bytecode::MStore(1),
+        Bytecode::Push(address.clone()),
+        Bytecode::MLoad(1),
+        Bytecode::Push(y.clone()),
+        Bytecode::Eq,
+        Bytecode::Assert,
+        Bytecode::Halt
+    ];
+    let init = || VecState::init().with_memory(SymbolicMemory::new());
+    let svm = StackMachine::<Term,SymbolicMemory<_>>::new(program(&x));
+    let vc = generate_vc(&svm, init()).unwrap();
+    assert_eq!(vc, Term::bool(true));
+    let svm = StackMachine::<Term,SymbolicMemory<_>>::new(program(&z));
+    let vc = generate_vc(&svm, init()).unwrap();
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert!(model.eval(&vc).is_zero());
+}
+
+#[test]
+fn test_08() {
+    // Storage accessed at unknown keys
+    let (k,v) = (Term::var("k",8),Term::var("v",8));
+    let bytecode = vec![
+        Bytecode::Push(k.clone()),
+        Bytecode::SLoad,
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Add,
+        Bytecode::Push(k.clone()),
+        Bytecode::SStore,
+        Bytecode::Push(k.clone()),
+        Bytecode::SLoad,
+        Bytecode::Push(v.clone()),
+        Bytecode::Push(Term::from_u64(8,1)),
+        Bytecode::Add,
+        Bytecode::Eq,
+        Bytecode::Assert,
+        Bytecode::Halt
+    ];
+    let mut storage = SymbolicStorage::new();
+    storage.store(k.clone(), v.clone()).unwrap();
+    let svm = StackMachine::<Term,WordMemory<_,_>,SymbolicStorage<_>>::new(bytecode);
+    let vc = generate_vc(&svm, VecState::init().with_storage(storage)).unwrap();
+    assert_eq!(check_validity(&vc), Response::Unsat);
+}
+
+#[test]
+fn test_09() {
+    // Memory accessed at unknown addresses must lie within its limit
+    let x = Term::var("x",8);
+    let bytecode = vec![Bytecode::Push(x.clone()), Bytecode::MLoad(2), Bytecode::Halt];
+    let svm = StackMachine::<Term,SymbolicMemory<_>>::new(bytecode);
+    let vc = generate_vc(&svm, VecState::init().with_memory(SymbolicMemory::new().max_size(64))).unwrap();
+    let Response::Sat(model) = check_validity(&vc) else { panic!() };
+    assert!(model.eval(&x).to_u64().unwrap() > 62);
+    let vc = generate_vc(&svm, VecState::init().with_memory(SymbolicMemory::new().max_size(257))).unwrap();
+    assert_eq!(vc, Term::bool(true));
+    let vc = generate_vc(&svm, VecState::init().with_memory(SymbolicMemory::new().max_size(256))).unwrap();
+    assert!(valid(&vc.clone().or(x.clone().equal(Term::from_u64(8,255)).nonzero())));
+    assert!(!holds(&vc, 255));
+}
+
+/// Initial state containing a single symbolic variable.
+fn init() -> VecState<Term> {
+    let mut s = VecState::init();
+    s.push(Term::var("x",8)).unwrap();
+    s
+}
+
+/// Check whether a given VC holds for a given value of `x`.
+fn holds(vc: &Term, x: u64) -> bool {
+    let v = vc.eval(&|_| Some(BitVec::from_u64(8,x))).unwrap();
+    !v.is_zero()
+}
+
+/// Check whether a given VC holds for all values of `x`.
+fn valid(vc: &Term) -> bool {
+    (0..256).all(|x| holds(vc,x))
+}
+
+// ===================================================================
+// Machine definition
+// ===================================================================
+
+/// A simple symbolic machine with assertions.
+pub struct AssertMachine {
+    code: Vec<Insn>
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Insn {
+    Push(u8),
+    Pop,
+    Dup,
+    Lt,
+    /// Branch to a given target if the top of the stack is non-zero.
+    JumpIf(usize),
+    Jump(usize),
+    /// Pop top of the stack, which must be non-zero.
+    Assert,
+    Ret
+}
+
+impl Machine for AssertMachine {
+    type Error = MinimalMachineError;
+    type State = VecState<Term>;
+    type Instruction = Insn;
+    type Outcome = StepOutcome<Self::State,Term>;
+
+    fn get(&self,pc: usize) -> Result<&Self::Instruction,Self::Error> {
+        self.code.get(pc).ok_or(MinimalMachineError::InvalidPC)
+    }
+
+    fn execute(&self, mut state: Self::State) -> Result<Self::Outcome,Self::Error> {
+        let pc = state.pc();
+        match self.get(pc)? {
+            Push(v) => {
+                state.push(Term::from_u64(8,*v as u64))?;
+            }
+            Pop|Assert => {
+                state.pop()?;
+            }
+            Dup => {
+                let v = state.peek(0)?.clone();
+                state.push(v)?;
+            }
+            Lt => {
+                let r = state.pop()?;
+                let l = state.pop()?;
+                state.push(l.less_than(r))?;
+            }
+            JumpIf(target) => {
+                let c = state.pop()?;
+                let mut other = state.clone();
+                other.goto(*target);
+                state.goto(pc+1);
+                return Ok(match c.as_constant() {
+                    Some(v) if v.is_zero() => StepOutcome::Continue(state),
+                    Some(_) => StepOutcome::Continue(other),
+                    None => StepOutcome::Fork(vec![state,other])
+                });
+            }
+            Jump(target) => {
+                state.goto(*target);
+                return Ok(StepOutcome::Continue(state));
+            }
+            Ret => { return Ok(StepOutcome::Return(state.pop()?)); }
+        }
+        state.goto(pc+1);
+        Ok(StepOutcome::Continue(state))
+    }
+}
+
+impl StepMachine for AssertMachine {
+    type Value = Term;
+}
+
+impl Verifiable for AssertMachine {
+    fn obligation(&self, state: &Self::State) -> Option<Term> {
+        match self.get(state.pc()) {
+            Ok(Assert) => state.peek(0).ok().cloned(),
+            _ => None
+        }
+    }
+
+    fn guard(&self, state: &Self::State, successor: &Self::State) -> Term {
+        let c = state.peek(0).unwrap().clone();
+        if successor.pc() == state.pc() + 1 { c.equal(Term::from_u64(8,0)) } else { c }
+    }
+}
@@ -0,0 +1,74 @@
+use vcg::{CallState,DEFAULT_MAX_DEPTH,Frame,MachineState,MinimalMachineError,VecState};
+
+#[test]
+fn test_01() {
+    let s = state(&[1,2,3]);
+    assert_eq!(s.peek(0), Ok(&3));
+    assert_eq!(s.peek(2), Ok(&1));
+    assert_eq!(s.peek(3), Err(MinimalMachineError::StackUnderflow));
+}
+
+#[test]
+fn test_02() {
+    let mut s = state(&[1,2,3]);
+    assert_eq!(s.set(0,4), Ok(3));
+    assert_eq!(s.set(2,5), Ok(1));
+    assert_eq!(s, state(&[5,2,4]));
+    assert_eq!(s.set(3,6), Err(MinimalMachineError::StackUnderflow));
+    assert_eq!(s, state(&[5,2,4]));
+}
+
+#[test]
+fn test_03() {
+    let mut s = state(&[1,2,3]);
+    assert_eq!(s.swap(2), Ok(()));
+    assert_eq!(s, state(&[3,2,1]));
+    assert_eq!(s.swap(1), Ok(()));
+    assert_eq!(s, state(&[3,1,2]));
+    assert_eq!(s.swap(0), Ok(()));
+    assert_eq!(s, state(&[3,1,2]));
+    assert_eq!(s.swap(3), Err(MinimalMachineError::StackUnderflow));
+    assert_eq!(VecState::<u8>::init().swap(0), Err(MinimalMachineError::StackUnderflow));
+}
+
+/// Construct a state from a given stack (whose last item is the top).
+fn state(items: &[u8]) -> VecState<u8> {
+    let mut s = VecState::init();
+    for i in items {
+        s.push(*i).unwrap();
+    }
+    s
+}
+
+#[test]
+fn test_04() {
+    let mut s = VecState::<u8>::init().max_depth(2);
+    assert_eq!(s.push(1), Ok(()));
+    assert_eq!(s.push(2), Ok(()));
+    assert_eq!(s.push(3), Err(MinimalMachineError::StackOverflow));
+    assert_eq!(s.size(), 2);
+    // Default depth is that of the EVM
+    let mut s = VecState::<u8>::init();
+    for i in 0..DEFAULT_MAX_DEPTH {
+        assert_eq!(s.push(i as u8), Ok(()));
+    }
+    assert_eq!(s.push(0), Err(MinimalMachineError::StackOverflow));
+}
+
+#[test]
+fn test_05() {
+    let mut s = VecState::<u8>::init().max_calls(2);
+    assert_eq!(s.depth(), 0);
+    assert_eq!(s.frame(), None);
+    assert_eq!(s.call(Frame::new(3, vec![1,2])), Ok(()));
+    assert_eq!(s.call(Frame::new(7, vec![])), Ok(()));
+    assert_eq!(s.call(Frame::new(9, vec![])), Err(MinimalMachineError::CallDepthExceeded));
+    assert_eq!(s.depth(), 2);
+    assert_eq!(s.ret().map(|f| f.return_pc()), Ok(7));
+    let f = s.frame_mut().unwrap();
+    assert_eq!(f.set(1, 5), Some(2));
+    assert_eq!(f.set(2, 5), None);
+    assert_eq!(s.frame().unwrap().locals(), &[1,5]);
+    assert_eq!(s.ret(), Ok(Frame::new(3, vec![1,5])));
+    assert_eq!(s.ret(), Err(MinimalMachineError::InvalidReturn));
+}
@@ -0,0 +1,111 @@
+use vcg::{BitVec,Interval,KnownBits,MachineWord,NativeWord,Term};
+
+#[test]
+fn test_01() {
+    assert_eq!(0xffu8.add(1), 0);
+    assert_eq!(0xffffu16.add(2), 1);
+    assert_eq!(u32::MAX.add(u32::MAX), u32::MAX - 1);
+    assert_eq!(u64::MAX.add(1), 0);
+    assert_eq!(u128::MAX.add(1), 0);
+    assert_eq!(usize::MAX.add(1), 0);
+}
+
+#[test]
+fn test_02() {
+    assert_eq!(0x10u8.mul(0x10), 0);
+    assert_eq!(0x0fu8.mul(0x03), 0x2d);
+    assert_eq!(0x100u16.mul(0x100), 0);
+    assert_eq!(u64::MAX.mul(u64::MAX), 1);
+    assert_eq!(u128::MAX.mul(2), u128::MAX - 1);
+}
+
+#[test]
+fn test_03() {
+    assert_eq!(0xffu8.div(0x10), 0x0f);
+    assert_eq!(0xffu8.rem(0x10), 0x0f);
+    assert_eq!(1000u16.div(7), 142);
+    assert_eq!(1000u16.rem(7), 6);
+    assert_eq!(u128::MAX.div(u128::MAX), 1);
+    assert_eq!(u128::MAX.rem(u128::MAX), 0);
+}
+
+#[test]
+fn test_04() {
+    // Division by zero
+    assert_eq!(0x12u8.div(0), 0);
+    assert_eq!(0x12u8.rem(0), 0);
+    assert_eq!(0x12u32.div(0), 0);
+    assert_eq!(0x12u32.rem(0), 0);
+    assert_eq!(u128::MAX.div(0), 0);
+    assert_eq!(usize::MAX.rem(0), 0);
+}
+
+#[test]
+fn test_05() {
+    assert_eq!(1u8.neg(), 0xff);
+    assert_eq!(0u8.neg(), 0);
+    assert_eq!(1u64.neg(), u64::MAX);
+    assert_eq!(0x8000u16.neg(), 0x8000);
+}
+
+#[test]
+fn test_06() {
+    assert_eq!(0xf0u8.and(0x3c), 0x30);
+    assert_eq!(0xf0u8.or(0x3c), 0xfc);
+    assert_eq!(0xf0u8.xor(0x3c), 0xcc);
+    assert_eq!(0xf0u8.not(), 0x0f);
+    assert_eq!(0u128.not(), u128::MAX);
+}
+
+#[test]
+fn test_07() {
+    assert_eq!(1u8.less_than(2), 1);
+    assert_eq!(2u8.less_than(1), 0);
+    assert_eq!(2u16.less_than(2), 0);
+    assert_eq!(2u32.equal(2), 1);
+    assert_eq!(2u64.equal(3), 0);
+    assert_eq!(usize::MAX.less_than(0), 0);
+}
+
+#[test]
+fn test_08() {
+    assert_eq!(0u8.sub(1), 0xff);
+    assert_eq!(5u16.sub(3), 2);
+    assert_eq!(0u128.sub(1), u128::MAX);
+    assert_eq!(BitVec::from_u64(3,1).sub(BitVec::from_u64(3,2)), BitVec::from_u64(3,7));
+}
+
+#[test]
+fn test_09() {
+    assert_eq!(0u8.to_bool(), Some(false));
+    assert_eq!(2u64.to_bool(), Some(true));
+    assert_eq!(BitVec::from_u64(3,4).to_bool(), Some(true));
+    assert_eq!(Term::from_u64(8,0).to_bool(), Some(false));
+    assert_eq!(Term::var("x",8).to_bool(), None);
+    let (zero,one,two) = (BitVec::zero(8), BitVec::from_u64(8,1), BitVec::from_u64(8,2));
+    assert_eq!(Interval::new(one.clone(),two.clone()).to_bool(), Some(true));
+    assert_eq!(Interval::new(zero.clone(),zero.clone()).to_bool(), Some(false));
+    assert_eq!(Interval::new(zero.clone(),one.clone()).to_bool(), None);
+    assert_eq!(KnownBits::constant(zero).to_bool(), Some(false));
+    assert_eq!(KnownBits::constant(two).to_bool(), Some(true));
+    assert_eq!(KnownBits::unknown(8).to_bool(), None);
+}
+
+#[test]
+fn test_10() {
+    assert_eq!(0xabu8.to_bitvec(), Some(BitVec::from_u64(8,0xab)));
+    assert_eq!(u128::MAX.to_bitvec(), Some(BitVec::ones(128)));
+    assert_eq!(Term::from_u64(8,3).to_bitvec(), Some(BitVec::from_u64(8,3)));
+    assert_eq!(Term::var("x",8).to_bitvec(), None);
+    let (one,two) = (BitVec::from_u64(8,1), BitVec::from_u64(8,2));
+    assert_eq!(Interval::new(one.clone(),one.clone()).to_bitvec(), Some(one.clone()));
+    assert_eq!(Interval::new(one,two).to_bitvec(), None);
+    assert_eq!(KnownBits::unknown(8).to_bitvec(), None);
+}
+
+#[test]
+fn test_11() {
+    assert_eq!(u8::WIDTH, 8);
+    assert_eq!(u128::WIDTH, 128);
+    assert_eq!(usize::WIDTH, usize::BITS as usize);
+}